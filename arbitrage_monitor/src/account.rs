@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+/// A recorded fill on one venue. Prices are dollars, sizes contracts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub ts_ms: i64,
+    pub venue: String,
+    pub market_id: String,
+    /// `true` for a buy, `false` for a sell.
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// A running position derived from fills, with realized PnL accrued as legs
+/// close out against the average cost.
+#[derive(Debug, Clone, Default)]
+pub struct Position {
+    /// Signed contract count: positive long, negative short.
+    pub net_size: f64,
+    /// Volume-weighted average cost of the open side, in dollars.
+    pub avg_cost: f64,
+    /// Realized PnL in dollars from closed quantity.
+    pub realized: f64,
+}
+
+impl Position {
+    /// Fold a fill into this position, realizing PnL on any quantity that
+    /// reduces or flips the existing side.
+    fn apply(&mut self, fill: &Fill) {
+        let signed = if fill.is_buy { fill.size } else { -fill.size };
+
+        if self.net_size == 0.0 || (self.net_size > 0.0) == (signed > 0.0) {
+            // Opening or adding to the same side: blend the average cost.
+            let total = self.net_size.abs() + signed.abs();
+            if total > 0.0 {
+                self.avg_cost =
+                    (self.avg_cost * self.net_size.abs() + fill.price * signed.abs()) / total;
+            }
+            self.net_size += signed;
+        } else {
+            // Reducing / closing: realize against the average cost.
+            let closing = signed.abs().min(self.net_size.abs());
+            let direction = if self.net_size > 0.0 { 1.0 } else { -1.0 };
+            self.realized += direction * (fill.price - self.avg_cost) * closing;
+            self.net_size += signed;
+            if (self.net_size > 0.0) != (direction > 0.0) && self.net_size != 0.0 {
+                // Position flipped: remaining quantity opens the other side.
+                self.avg_cost = fill.price;
+            }
+        }
+    }
+
+    /// Unrealized PnL marking the open position to `mark` (dollars).
+    pub fn unrealized(&self, mark: f64) -> f64 {
+        self.net_size * (mark - self.avg_cost)
+    }
+}
+
+/// Persistent activity log plus the positions reconciled from it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Account {
+    pub fills: Vec<Fill>,
+}
+
+impl Account {
+    /// Load the activity log from `path`, or start empty if it does not exist.
+    pub fn load(path: &str) -> Result<Account> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).context("Failed to parse activity log"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Account::default()),
+            Err(e) => Err(e).context("Failed to read activity log"),
+        }
+    }
+
+    /// Persist the activity log so PnL survives restarts.
+    pub fn save(&self, path: &str) -> Result<()> {
+        let text = serde_json::to_string_pretty(self).context("Failed to serialize activity log")?;
+        std::fs::write(path, text).context("Failed to write activity log")?;
+        Ok(())
+    }
+
+    /// Append a fill to the log.
+    pub fn record(&mut self, fill: Fill) {
+        self.fills.push(fill);
+    }
+
+    /// Reconcile the fill log into per-market positions.
+    pub fn positions(&self) -> std::collections::HashMap<(String, String), Position> {
+        let mut positions: std::collections::HashMap<(String, String), Position> =
+            std::collections::HashMap::new();
+        for fill in &self.fills {
+            positions
+                .entry((fill.venue.clone(), fill.market_id.clone()))
+                .or_default()
+                .apply(fill);
+        }
+        positions
+    }
+
+    /// Total realized PnL (dollars) across all positions.
+    pub fn realized(&self) -> f64 {
+        self.positions().values().map(|p| p.realized).sum()
+    }
+}
+
+/// A venue-reported holding, used to reconcile the locally derived position
+/// against what the exchange actually shows.
+#[derive(Debug, Clone)]
+pub struct ReportedPosition {
+    pub venue: String,
+    pub market_id: String,
+    /// Signed contract count as reported by the venue.
+    pub net_size: f64,
+}
+
+/// The live account state fetched from a venue for reconciliation.
+#[derive(Debug, Clone, Default)]
+pub struct VenueState {
+    /// Available balance in dollars.
+    pub balance: f64,
+    pub positions: Vec<ReportedPosition>,
+}
+
+/// Render a PnL panel, marking open positions to the supplied per-market marks.
+/// When `venue` is supplied, also show the exchange-reported balance and flag
+/// any position whose derived size disagrees with the venue's own count.
+pub fn render_pnl_panel(
+    account: &Account,
+    marks: &std::collections::HashMap<(String, String), f64>,
+    venue: Option<&VenueState>,
+) {
+    let positions = account.positions();
+    let realized: f64 = positions.values().map(|p| p.realized).sum();
+    let unrealized: f64 = positions
+        .iter()
+        .filter_map(|(key, pos)| marks.get(key).map(|m| pos.unrealized(*m)))
+        .sum();
+
+    println!("\n{}", "─".repeat(70).cyan());
+    println!("{}", "💼 PnL".bold());
+    for ((venue, market), pos) in &positions {
+        if pos.net_size.abs() < 1e-9 {
+            continue;
+        }
+        let mark = marks.get(&(venue.clone(), market.clone())).copied().unwrap_or(pos.avg_cost);
+        println!(
+            "  {:<10} {:<22} size {:>8.0} @ ${:.4}  uPnL ${:.2}",
+            venue,
+            market,
+            pos.net_size,
+            pos.avg_cost,
+            pos.unrealized(mark)
+        );
+    }
+    println!(
+        "  {} ${:.2}   {} ${:.2}",
+        "Realized:".green(),
+        realized,
+        "Unrealized:".yellow(),
+        unrealized
+    );
+
+    if let Some(state) = venue {
+        println!("{}", "─".repeat(70).dimmed());
+        println!("  {} ${:.2}", "Venue balance:".cyan(), state.balance);
+        for reported in &state.positions {
+            let key = (reported.venue.clone(), reported.market_id.clone());
+            let derived = positions.get(&key).map(|p| p.net_size).unwrap_or(0.0);
+            let drift = (derived - reported.net_size).abs() > 1e-9;
+            let line = format!(
+                "  {:<10} {:<22} venue {:>8.0}  derived {:>8.0}",
+                reported.venue, reported.market_id, reported.net_size, derived
+            );
+            println!("{}", if drift { line.red().to_string() } else { line });
+        }
+    }
+
+    println!("{}", "─".repeat(70).cyan());
+}
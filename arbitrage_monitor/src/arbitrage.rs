@@ -1,57 +1,250 @@
+use crate::fees::FeeConfig;
 use crate::kalshi::KalshiMarket;
 use crate::polymarket::PolymarketMarket;
 
+/// Default number of book levels walked when no `--depth` cap is supplied.
+pub const DEFAULT_DEPTH: usize = usize::MAX;
+
 #[derive(Debug)]
 pub struct ArbitrageOpportunity {
     pub buy_platform: String,
     pub sell_platform: String,
+    /// Volume-weighted average price paid on the buy venue, in dollars.
     pub buy_price: f64,
+    /// Volume-weighted average price received on the sell venue, in dollars.
     pub sell_price: f64,
+    /// Executable size (contracts) before the spread closes or depth runs out.
+    pub max_size: f64,
+    /// Summed gross profit in cents across every matched contract.
+    pub total_profit: f64,
+    /// Round-trip trading fees in cents (both legs).
+    pub fees: f64,
+    /// Summed profit in cents after subtracting round-trip fees.
+    pub net_profit: f64,
     pub profit_cents: f64,
     pub profit_pct: f64,
 }
 
-/// Detect arbitrage opportunities between Kalshi and Polymarket
+/// A normalized book side: ascending asks (cheapest first) or descending bids
+/// (richest first), prices in dollars.
+struct Levels {
+    /// (price_in_dollars, size_in_contracts)
+    entries: Vec<(f64, f64)>,
+}
+
+impl Levels {
+    fn kalshi_yes_bids(market: &KalshiMarket) -> Self {
+        let mut entries: Vec<(f64, f64)> = market
+            .orderbook
+            .as_ref()
+            .map(|ob| {
+                ob.yes
+                    .iter()
+                    .map(|(p, s)| (*p as f64 / 100.0, *s as f64))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if entries.is_empty() && market.yes_bid > 0 {
+            entries.push((market.yes_bid as f64 / 100.0, f64::MAX));
+        }
+        entries.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        Self { entries }
+    }
+
+    fn kalshi_yes_asks(market: &KalshiMarket) -> Self {
+        // A yes ask sits at `100 - no_bid`; the resting size is the no bid size.
+        let mut entries: Vec<(f64, f64)> = market
+            .orderbook
+            .as_ref()
+            .map(|ob| {
+                ob.no
+                    .iter()
+                    .map(|(p, s)| ((100 - *p) as f64 / 100.0, *s as f64))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if entries.is_empty() && market.yes_ask > 0 {
+            entries.push((market.yes_ask as f64 / 100.0, f64::MAX));
+        }
+        entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        Self { entries }
+    }
+
+    fn poly_bids(market: &PolymarketMarket) -> Self {
+        let mut entries = parse_poly(&market.bids);
+        if entries.is_empty() && market.best_bid > 0.0 {
+            entries.push((market.best_bid, f64::MAX));
+        }
+        entries.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        Self { entries }
+    }
+
+    fn poly_asks(market: &PolymarketMarket) -> Self {
+        let mut entries = parse_poly(&market.asks);
+        if entries.is_empty() && market.best_ask > 0.0 {
+            entries.push((market.best_ask, f64::MAX));
+        }
+        entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        Self { entries }
+    }
+}
+
+fn parse_poly(levels: &[crate::polymarket::OrderbookLevel]) -> Vec<(f64, f64)> {
+    levels
+        .iter()
+        .filter_map(|l| Some((l.price.parse::<f64>().ok()?, l.size.parse::<f64>().ok()?)))
+        .collect()
+}
+
+/// Depth-aware matcher shared by the two-venue ([`detect_arbitrage`]) and the
+/// multi-venue ([`crate::exchange`]) engines. Walks `buy_asks` (cheapest first)
+/// against `sell_bids` (richest first), matching quantity level-by-level while
+/// `sell_price > buy_price`, capping at `depth` distinct price levels per side
+/// and netting each leg's fee (in cents) via the supplied closures. Returns
+/// `None` unless the cross is net-positive after fees.
+pub(crate) fn match_cross(
+    buy_asks: &[(f64, f64)],
+    sell_bids: &[(f64, f64)],
+    buy_platform: &str,
+    sell_platform: &str,
+    depth: usize,
+    buy_fee: &dyn Fn(f64, f64) -> f64,
+    sell_fee: &dyn Fn(f64, f64) -> f64,
+) -> Option<ArbitrageOpportunity> {
+    let mut bi = 0usize;
+    let mut si = 0usize;
+    // Remaining size at the current level on each side.
+    let mut buy_rem = buy_asks.first().map(|l| l.1).unwrap_or(0.0);
+    let mut sell_rem = sell_bids.first().map(|l| l.1).unwrap_or(0.0);
+
+    let mut filled = 0.0f64;
+    let mut total_profit = 0.0f64; // cents
+    let mut buy_notional = 0.0f64;
+    let mut sell_notional = 0.0f64;
+
+    // `depth` caps how many distinct price levels we may consume on each side,
+    // not how many match-steps we take: one level spanning several opposing
+    // levels still counts as a single level here.
+    while bi < buy_asks.len() && si < sell_bids.len() && bi < depth && si < depth {
+        let buy_price = buy_asks[bi].0;
+        let sell_price = sell_bids[si].0;
+        if sell_price <= buy_price {
+            break;
+        }
+
+        let qty = buy_rem.min(sell_rem);
+        if qty <= 0.0 {
+            break;
+        }
+
+        filled += qty;
+        buy_notional += buy_price * qty;
+        sell_notional += sell_price * qty;
+        total_profit += (sell_price - buy_price) * qty * 100.0;
+
+        buy_rem -= qty;
+        sell_rem -= qty;
+        if buy_rem <= 0.0 {
+            bi += 1;
+            buy_rem = buy_asks.get(bi).map(|l| l.1).unwrap_or(0.0);
+        }
+        if sell_rem <= 0.0 {
+            si += 1;
+            sell_rem = sell_bids.get(si).map(|l| l.1).unwrap_or(0.0);
+        }
+    }
+
+    if filled <= 0.0 {
+        return None;
+    }
+
+    let vwap_buy = buy_notional / filled;
+    let vwap_sell = sell_notional / filled;
+
+    let fee_total = buy_fee(filled, vwap_buy) + sell_fee(filled, vwap_sell);
+    let net_profit = total_profit - fee_total;
+
+    // Only a genuinely net-positive cross is an opportunity.
+    if net_profit <= 0.0 {
+        return None;
+    }
+
+    Some(ArbitrageOpportunity {
+        buy_platform: buy_platform.to_string(),
+        sell_platform: sell_platform.to_string(),
+        buy_price: vwap_buy,
+        sell_price: vwap_sell,
+        max_size: filled,
+        total_profit,
+        fees: fee_total,
+        net_profit,
+        profit_cents: (vwap_sell - vwap_buy) * 100.0,
+        profit_pct: (vwap_sell - vwap_buy) / vwap_buy * 100.0,
+    })
+}
+
+/// Match the buy venue's asks against the sell venue's bids for the two-venue
+/// engine, charging each leg through the [`FeeConfig`] for its platform.
+fn walk(
+    buy: &Levels,
+    sell: &Levels,
+    buy_platform: &str,
+    sell_platform: &str,
+    depth: usize,
+    fees: &FeeConfig,
+) -> Option<ArbitrageOpportunity> {
+    // Fees apply per leg at that leg's venue, price, and size.
+    let leg_fee = |platform: &str, contracts: f64, price: f64| -> f64 {
+        match platform {
+            "Kalshi" => fees.kalshi_fee_cents(contracts, price),
+            "Polymarket" => fees.polymarket_fee_cents(contracts, price),
+            _ => 0.0,
+        }
+    };
+    match_cross(
+        &buy.entries,
+        &sell.entries,
+        buy_platform,
+        sell_platform,
+        depth,
+        &|c, p| leg_fee(buy_platform, c, p),
+        &|c, p| leg_fee(sell_platform, c, p),
+    )
+}
+
+/// Detect depth-aware arbitrage between Kalshi and Polymarket, considering at
+/// most `depth` crossing levels on each side.
 pub fn detect_arbitrage(
     kalshi: &KalshiMarket,
     polymarket: &PolymarketMarket,
+    depth: usize,
+    fees: &FeeConfig,
 ) -> Option<ArbitrageOpportunity> {
-    // Convert Kalshi cents to dollars for comparison
-    let kalshi_bid = kalshi.yes_bid as f64 / 100.0;
-    let kalshi_ask = kalshi.yes_ask as f64 / 100.0;
-    
-    let poly_bid = polymarket.best_bid;
-    let poly_ask = polymarket.best_ask;
-
-    // Check if we can buy on Polymarket and sell on Kalshi
-    if kalshi_bid > poly_ask && poly_ask > 0.0 {
-        let profit = kalshi_bid - poly_ask;
-        let profit_pct = (profit / poly_ask) * 100.0;
-        
-        return Some(ArbitrageOpportunity {
-            buy_platform: "Polymarket".to_string(),
-            sell_platform: "Kalshi".to_string(),
-            buy_price: poly_ask,
-            sell_price: kalshi_bid,
-            profit_cents: profit * 100.0,
-            profit_pct,
-        });
-    }
-
-    // Check if we can buy on Kalshi and sell on Polymarket
-    if poly_bid > kalshi_ask && kalshi_ask > 0.0 {
-        let profit = poly_bid - kalshi_ask;
-        let profit_pct = (profit / kalshi_ask) * 100.0;
-        
-        return Some(ArbitrageOpportunity {
-            buy_platform: "Kalshi".to_string(),
-            sell_platform: "Polymarket".to_string(),
-            buy_price: kalshi_ask,
-            sell_price: poly_bid,
-            profit_cents: profit * 100.0,
-            profit_pct,
-        });
-    }
-
-    None
+    // Buy on Polymarket (asks), sell on Kalshi (yes bids).
+    let p_to_k = walk(
+        &Levels::poly_asks(polymarket),
+        &Levels::kalshi_yes_bids(kalshi),
+        "Polymarket",
+        "Kalshi",
+        depth,
+        fees,
+    );
+
+    // Buy on Kalshi (yes asks), sell on Polymarket (bids).
+    let k_to_p = walk(
+        &Levels::kalshi_yes_asks(kalshi),
+        &Levels::poly_bids(polymarket),
+        "Kalshi",
+        "Polymarket",
+        depth,
+        fees,
+    );
+
+    // Prefer the direction with the larger realizable net profit.
+    match (p_to_k, k_to_p) {
+        (Some(a), Some(b)) => Some(if a.net_profit >= b.net_profit { a } else { b }),
+        (Some(a), None) => Some(a),
+        (None, Some(b)) => Some(b),
+        (None, None) => None,
+    }
 }
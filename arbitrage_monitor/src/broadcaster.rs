@@ -0,0 +1,320 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use futures_util::{SinkExt, StreamExt};
+use ordered_float::OrderedFloat;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::polymarket::{PolymarketClient, PolymarketEvent, PolymarketMarket};
+
+/// A per-asset order book maintained from the upstream event feed, used to
+/// fold successive `book`/`price_change` events into a single checkpoint.
+#[derive(Default)]
+pub(crate) struct AssetBook {
+    bids: BTreeMap<OrderedFloat<f64>, f64>,
+    asks: BTreeMap<OrderedFloat<f64>, f64>,
+}
+
+impl AssetBook {
+    /// Fold one typed event into the book. Full `book` snapshots reseed; a
+    /// `price_change` sets or clears individual levels. Other events are
+    /// checkpoint-neutral and ignored.
+    pub(crate) fn apply(&mut self, event: &PolymarketEvent) {
+        match event {
+            PolymarketEvent::Book { bids, asks, .. } => {
+                self.bids.clear();
+                self.asks.clear();
+                for level in bids {
+                    if let (Ok(p), Ok(s)) = (level.price.parse(), level.size.parse()) {
+                        self.bids.insert(OrderedFloat(p), s);
+                    }
+                }
+                for level in asks {
+                    if let (Ok(p), Ok(s)) = (level.price.parse(), level.size.parse()) {
+                        self.asks.insert(OrderedFloat(p), s);
+                    }
+                }
+            }
+            PolymarketEvent::PriceChange { changes, .. } => {
+                for change in changes {
+                    let Ok(price) = change.price.parse::<f64>() else {
+                        continue;
+                    };
+                    let size = change.size.parse::<f64>().unwrap_or(0.0);
+                    let book = if change.side.eq_ignore_ascii_case("sell")
+                        || change.side.eq_ignore_ascii_case("ask")
+                    {
+                        &mut self.asks
+                    } else {
+                        &mut self.bids
+                    };
+                    if size == 0.0 {
+                        book.remove(&OrderedFloat(price));
+                    } else {
+                        book.insert(OrderedFloat(price), size);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Project the book into a [`PolymarketMarket`] checkpoint for `asset_id`.
+    pub(crate) fn checkpoint(&self, asset_id: &str) -> PolymarketMarket {
+        use crate::polymarket::OrderbookLevel;
+        let bids: Vec<OrderbookLevel> = self
+            .bids
+            .iter()
+            .rev()
+            .map(|(p, s)| OrderbookLevel { price: p.0.to_string(), size: s.to_string() })
+            .collect();
+        let asks: Vec<OrderbookLevel> = self
+            .asks
+            .iter()
+            .map(|(p, s)| OrderbookLevel { price: p.0.to_string(), size: s.to_string() })
+            .collect();
+        let best_bid = self.bids.keys().next_back().map(|p| p.0).unwrap_or(0.0);
+        let best_ask = self.asks.keys().next().map(|p| p.0).unwrap_or(0.0);
+        PolymarketMarket { token_id: asset_id.to_string(), best_bid, best_ask, bids, asks }
+    }
+}
+
+/// A connected local consumer: the channel its writer task drains, and the set
+/// of asset ids it has subscribed to.
+struct Peer {
+    tx: mpsc::UnboundedSender<Message>,
+    assets: HashSet<String>,
+}
+
+/// State shared between the upstream pump and every peer task.
+#[derive(Default)]
+struct Shared {
+    /// Latest checkpoint per asset id.
+    checkpoints: HashMap<String, PolymarketMarket>,
+    /// Connected peers, keyed by a monotonic id.
+    peers: HashMap<u64, Peer>,
+}
+
+/// Commands a local consumer may send over its WebSocket.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command")]
+enum Command {
+    #[serde(rename = "subscribe", rename_all = "camelCase")]
+    Subscribe { asset_id: String },
+    #[serde(rename = "unsubscribe", rename_all = "camelCase")]
+    Unsubscribe { asset_id: String },
+    #[serde(rename = "getMarkets")]
+    GetMarkets,
+}
+
+/// Fans one upstream Polymarket connection out to many local consumers.
+///
+/// A single upstream [`PolymarketClient`] feeds a per-asset checkpoint map;
+/// local dashboards and bots connect over plain WebSocket, subscribe to the
+/// asset ids they care about, and receive the current checkpoint followed by a
+/// live stream of updates — so N consumers cost Polymarket exactly one
+/// connection.
+pub struct PolymarketBroadcaster {
+    upstream: PolymarketClient,
+    bind_addr: String,
+    debug: bool,
+}
+
+impl PolymarketBroadcaster {
+    /// Wrap `upstream` and serve local consumers on `bind_addr` (e.g.
+    /// `127.0.0.1:8765`).
+    pub fn new(upstream: PolymarketClient, bind_addr: String) -> Self {
+        Self { upstream, bind_addr, debug: false }
+    }
+
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    /// Run the broadcaster until the listener fails. Spawns the upstream pump,
+    /// then accepts local consumers forever.
+    pub async fn run(self) -> Result<()> {
+        let shared: Arc<Mutex<Shared>> = Arc::new(Mutex::new(Shared::default()));
+        let debug = self.debug;
+
+        let pump_shared = shared.clone();
+        tokio::spawn(async move {
+            Self::pump_upstream(self.upstream, pump_shared, debug).await;
+        });
+
+        let listener = TcpListener::bind(&self.bind_addr)
+            .await
+            .with_context(|| format!("Failed to bind {}", self.bind_addr))?;
+        println!("{}", format!("📡 Broadcasting on ws://{}", self.bind_addr).bold().green());
+
+        let next_id = Arc::new(AtomicU64::new(0));
+        loop {
+            let (stream, addr) = listener.accept().await.context("accept failed")?;
+            let id = next_id.fetch_add(1, Ordering::Relaxed);
+            let peer_shared = shared.clone();
+            tokio::spawn(async move {
+                if let Err(e) = Self::serve_peer(stream, id, peer_shared.clone(), debug).await {
+                    if debug {
+                        println!("{}", format!("peer {} ({}) ended: {}", id, addr, e).yellow());
+                    }
+                }
+                // The peer is gone; drop it so the pump stops fanning out to it.
+                peer_shared.lock().await.peers.remove(&id);
+            });
+        }
+    }
+
+    /// Drive the upstream connection, reconnecting with exponential backoff, and
+    /// fan every demultiplexed event out to the peers subscribed to its asset.
+    async fn pump_upstream(mut upstream: PolymarketClient, shared: Arc<Mutex<Shared>>, debug: bool) {
+        const INITIAL: Duration = Duration::from_secs(1);
+        const MAX: Duration = Duration::from_secs(60);
+        let mut backoff = INITIAL;
+        let mut books: HashMap<String, AssetBook> = HashMap::new();
+
+        loop {
+            match upstream.connect().await {
+                Ok(()) => loop {
+                    match upstream.read_next_event().await {
+                        Ok((asset_id, event)) => {
+                            backoff = INITIAL;
+                            let book = books.entry(asset_id.clone()).or_default();
+                            book.apply(&event);
+                            let checkpoint = book.checkpoint(&asset_id);
+                            let payload = market_message("update", &checkpoint);
+
+                            let mut guard = shared.lock().await;
+                            guard.checkpoints.insert(asset_id.clone(), checkpoint);
+                            for peer in guard.peers.values() {
+                                if peer.assets.contains(&asset_id) {
+                                    let _ = peer.tx.send(Message::Text(payload.to_string()));
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            if debug {
+                                println!("{}", format!("upstream dropped: {}", e).yellow());
+                            }
+                            break;
+                        }
+                    }
+                },
+                Err(e) => {
+                    if debug {
+                        println!("{}", format!("upstream connect failed: {}", e).yellow());
+                    }
+                }
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX);
+        }
+    }
+
+    /// Accept one local consumer, register it, and service its commands until it
+    /// disconnects. A dedicated writer task drains the peer's channel into the
+    /// socket so the command loop never blocks on a slow consumer.
+    async fn serve_peer(
+        stream: TcpStream,
+        id: u64,
+        shared: Arc<Mutex<Shared>>,
+        debug: bool,
+    ) -> Result<()> {
+        let ws = tokio_tungstenite::accept_async(stream)
+            .await
+            .context("WebSocket handshake failed")?;
+        let (mut write, mut read) = ws.split();
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+        shared
+            .lock()
+            .await
+            .peers
+            .insert(id, Peer { tx: tx.clone(), assets: HashSet::new() });
+
+        // Writer task: forward queued messages to the socket.
+        let writer = tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                if write.send(msg).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(msg) = read.next().await {
+            let text = match msg {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) | Err(_) => break,
+                Ok(_) => continue,
+            };
+            let Ok(command) = serde_json::from_str::<Command>(&text) else {
+                if debug {
+                    println!("{}", format!("peer {} sent bad command: {}", id, text).yellow());
+                }
+                continue;
+            };
+            Self::handle_command(id, command, &shared, &tx).await;
+        }
+
+        writer.abort();
+        Ok(())
+    }
+
+    /// Apply one command for peer `id`, replying on `tx` where the protocol
+    /// calls for an immediate response.
+    async fn handle_command(
+        id: u64,
+        command: Command,
+        shared: &Arc<Mutex<Shared>>,
+        tx: &mpsc::UnboundedSender<Message>,
+    ) {
+        let mut guard = shared.lock().await;
+        match command {
+            Command::Subscribe { asset_id } => {
+                if let Some(peer) = guard.peers.get_mut(&id) {
+                    peer.assets.insert(asset_id.clone());
+                }
+                // Send the current checkpoint right away, if we have one.
+                if let Some(market) = guard.checkpoints.get(&asset_id) {
+                    let payload = market_message("checkpoint", market);
+                    let _ = tx.send(Message::Text(payload.to_string()));
+                }
+            }
+            Command::Unsubscribe { asset_id } => {
+                if let Some(peer) = guard.peers.get_mut(&id) {
+                    peer.assets.remove(&asset_id);
+                }
+            }
+            Command::GetMarkets => {
+                let assets: Vec<&String> = guard.checkpoints.keys().collect();
+                let payload = json!({ "type": "markets", "assets": assets });
+                let _ = tx.send(Message::Text(payload.to_string()));
+            }
+        }
+    }
+}
+
+/// Serialize a checkpoint into the wire message sent to consumers.
+fn market_message(kind: &str, market: &PolymarketMarket) -> serde_json::Value {
+    let levels = |levels: &[crate::polymarket::OrderbookLevel]| {
+        levels
+            .iter()
+            .map(|l| json!({ "price": l.price, "size": l.size }))
+            .collect::<Vec<_>>()
+    };
+    json!({
+        "type": kind,
+        "assetId": market.token_id,
+        "bestBid": market.best_bid,
+        "bestAsk": market.best_ask,
+        "bids": levels(&market.bids),
+        "asks": levels(&market.asks),
+    })
+}
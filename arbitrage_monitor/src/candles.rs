@@ -0,0 +1,312 @@
+use anyhow::{Context, Result};
+use tokio_postgres::Client;
+
+use crate::storage::SnapshotRow;
+
+/// Candle resolutions, coarsest built by rolling up the finished `1m` candles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    M1,
+    M5,
+    M15,
+    H1,
+    D1,
+}
+
+impl Resolution {
+    /// Bucket width in seconds.
+    pub fn secs(self) -> i64 {
+        match self {
+            Resolution::M1 => 60,
+            Resolution::M5 => 5 * 60,
+            Resolution::M15 => 15 * 60,
+            Resolution::H1 => 60 * 60,
+            Resolution::D1 => 24 * 60 * 60,
+        }
+    }
+
+    /// Canonical label stored in the `resolution` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resolution::M1 => "1m",
+            Resolution::M5 => "5m",
+            Resolution::M15 => "15m",
+            Resolution::H1 => "1h",
+            Resolution::D1 => "1d",
+        }
+    }
+
+    /// Resolutions built by aggregating the `1m` candle series.
+    pub fn coarser() -> [Resolution; 4] {
+        [Resolution::M5, Resolution::M15, Resolution::H1, Resolution::D1]
+    }
+}
+
+/// Start of the bucket containing `ts_secs` at resolution `res`.
+pub fn bucket_start(ts_secs: i64, res: Resolution) -> i64 {
+    (ts_secs / res.secs()) * res.secs()
+}
+
+/// An OHLC candle keyed by `(market_id, resolution, bucket_start)`.
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub market_id: String,
+    pub resolution: Resolution,
+    pub bucket_start: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl Candle {
+    fn seed(market_id: &str, resolution: Resolution, bucket_start: i64, price: f64, size: f64) -> Self {
+        Self {
+            market_id: market_id.to_string(),
+            resolution,
+            bucket_start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: size,
+        }
+    }
+
+    /// Fold a later tick in the same bucket into this candle.
+    fn update(&mut self, price: f64, size: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += size;
+    }
+
+    /// Fold a finished finer-grained candle into this coarser candle.
+    fn merge(&mut self, sub: &Candle) {
+        self.high = self.high.max(sub.high);
+        self.low = self.low.min(sub.low);
+        self.close = sub.close;
+        self.volume += sub.volume;
+    }
+}
+
+/// Stateful accumulator that turns a stream of price ticks into candles at
+/// every resolution. `1m` is driven directly from ticks; coarser candles are
+/// rolled up from each finished `1m` candle.
+#[derive(Default)]
+pub struct CandleBuilder {
+    current: std::collections::HashMap<(String, Resolution), Candle>,
+}
+
+impl CandleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ingest a tick. Returns any candles that were finalized (bucket rolled
+    /// over) as a result, ready to be upserted.
+    pub fn ingest(&mut self, market_id: &str, ts_secs: i64, price: f64, size: f64) -> Vec<Candle> {
+        let mut finished = Vec::new();
+        self.apply(market_id, Resolution::M1, ts_secs, price, size, &mut finished);
+
+        // Any finished 1m candle feeds the coarser resolutions.
+        let minute_candles: Vec<Candle> = finished
+            .iter()
+            .filter(|c| c.resolution == Resolution::M1)
+            .cloned()
+            .collect();
+        for minute in minute_candles {
+            for res in Resolution::coarser() {
+                self.roll_up(&minute, res, &mut finished);
+            }
+        }
+        finished
+    }
+
+    fn apply(
+        &mut self,
+        market_id: &str,
+        res: Resolution,
+        ts_secs: i64,
+        price: f64,
+        size: f64,
+        finished: &mut Vec<Candle>,
+    ) {
+        let bucket = bucket_start(ts_secs, res);
+        let key = (market_id.to_string(), res);
+        match self.current.get_mut(&key) {
+            Some(candle) if candle.bucket_start == bucket => candle.update(price, size),
+            Some(candle) if candle.bucket_start < bucket => {
+                finished.push(candle.clone());
+                *candle = Candle::seed(market_id, res, bucket, price, size);
+            }
+            _ => {
+                self.current
+                    .insert(key, Candle::seed(market_id, res, bucket, price, size));
+            }
+        }
+    }
+
+    fn roll_up(&mut self, minute: &Candle, res: Resolution, finished: &mut Vec<Candle>) {
+        let bucket = bucket_start(minute.bucket_start, res);
+        let key = (minute.market_id.clone(), res);
+        match self.current.get_mut(&key) {
+            Some(candle) if candle.bucket_start == bucket => candle.merge(minute),
+            Some(candle) if candle.bucket_start < bucket => {
+                finished.push(candle.clone());
+                let mut seeded =
+                    Candle::seed(&minute.market_id, res, bucket, minute.open, 0.0);
+                seeded.merge(minute);
+                *candle = seeded;
+            }
+            _ => {
+                let mut seeded =
+                    Candle::seed(&minute.market_id, res, bucket, minute.open, 0.0);
+                seeded.merge(minute);
+                self.current.insert(key, seeded);
+            }
+        }
+    }
+}
+
+/// Postgres-backed candle store. Writes go through a single batched upsert.
+pub struct CandleStore {
+    client: Client,
+}
+
+impl CandleStore {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Create the candle table if it does not yet exist.
+    pub async fn init(&self) -> Result<()> {
+        self.client
+            .batch_execute(SCHEMA)
+            .await
+            .context("Failed to initialize candle schema")?;
+        Ok(())
+    }
+
+    /// Record a raw book snapshot, the source series [`backfill`] replays.
+    pub async fn append_snapshot(&self, row: &SnapshotRow) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO snapshots (ts_ms, venue, market_id, best_bid, best_ask, depth, spread) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &row.ts_ms,
+                    &row.venue,
+                    &row.market_id,
+                    &row.best_bid,
+                    &row.best_ask,
+                    &row.depth,
+                    &row.spread,
+                ],
+            )
+            .await
+            .context("Failed to append snapshot")?;
+        Ok(())
+    }
+
+    /// Upsert a batch of candles in a single multi-row statement, merging OHLC
+    /// fields against any existing row for the same key.
+    pub async fn upsert(&self, candles: &[Candle]) -> Result<()> {
+        if candles.is_empty() {
+            return Ok(());
+        }
+
+        let mut sql = String::from(
+            "INSERT INTO candles (market_id, resolution, bucket_start, open, high, low, close, volume) VALUES ",
+        );
+        let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
+        let resolutions: Vec<String> = candles.iter().map(|c| c.resolution.as_str().to_string()).collect();
+
+        for (i, c) in candles.iter().enumerate() {
+            if i > 0 {
+                sql.push_str(", ");
+            }
+            let b = i * 8;
+            sql.push_str(&format!(
+                "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                b + 1, b + 2, b + 3, b + 4, b + 5, b + 6, b + 7, b + 8
+            ));
+            params.push(&c.market_id);
+            params.push(&resolutions[i]);
+            params.push(&c.bucket_start);
+            params.push(&c.open);
+            params.push(&c.high);
+            params.push(&c.low);
+            params.push(&c.close);
+            params.push(&c.volume);
+        }
+
+        sql.push_str(
+            " ON CONFLICT (market_id, resolution, bucket_start) DO UPDATE SET \
+             high = GREATEST(candles.high, EXCLUDED.high), \
+             low = LEAST(candles.low, EXCLUDED.low), \
+             close = EXCLUDED.close, \
+             volume = candles.volume + EXCLUDED.volume",
+        );
+
+        self.client
+            .execute(sql.as_str(), &params)
+            .await
+            .context("Failed to upsert candles")?;
+        Ok(())
+    }
+}
+
+/// Reconstruct candles for a market over `[start_secs, end_secs)` by replaying
+/// stored raw snapshots through a fresh [`CandleBuilder`]. Mid price is used as
+/// the tick price and the summed depth as the tick volume.
+pub async fn backfill(store: &CandleStore, market_id: &str, start_secs: i64, end_secs: i64) -> Result<()> {
+    let rows = store
+        .client
+        .query(
+            "SELECT ts_ms, best_bid, best_ask, depth FROM snapshots \
+             WHERE market_id = $1 AND ts_ms >= $2 AND ts_ms < $3 ORDER BY ts_ms ASC",
+            &[&market_id, &(start_secs * 1000), &(end_secs * 1000)],
+        )
+        .await
+        .context("Failed to read snapshots for backfill")?;
+
+    let mut builder = CandleBuilder::new();
+    let mut batch = Vec::new();
+    for row in rows {
+        let ts_ms: i64 = row.get(0);
+        let best_bid: f64 = row.get(1);
+        let best_ask: f64 = row.get(2);
+        let depth: f64 = row.get(3);
+        let mid = (best_bid + best_ask) / 2.0;
+        batch.extend(builder.ingest(market_id, ts_ms / 1000, mid, depth));
+    }
+    store.upsert(&batch).await?;
+    Ok(())
+}
+
+const SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS candles (
+    market_id    TEXT NOT NULL,
+    resolution   TEXT NOT NULL,
+    bucket_start BIGINT NOT NULL,
+    open         DOUBLE PRECISION NOT NULL,
+    high         DOUBLE PRECISION NOT NULL,
+    low          DOUBLE PRECISION NOT NULL,
+    close        DOUBLE PRECISION NOT NULL,
+    volume       DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (market_id, resolution, bucket_start)
+);
+CREATE TABLE IF NOT EXISTS snapshots (
+    id         BIGSERIAL PRIMARY KEY,
+    ts_ms      BIGINT NOT NULL,
+    venue      TEXT NOT NULL,
+    market_id  TEXT NOT NULL,
+    best_bid   DOUBLE PRECISION NOT NULL,
+    best_ask   DOUBLE PRECISION NOT NULL,
+    depth      DOUBLE PRECISION NOT NULL,
+    spread     DOUBLE PRECISION NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_snapshots_market_ts ON snapshots (market_id, ts_ms);
+";
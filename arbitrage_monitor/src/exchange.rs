@@ -0,0 +1,192 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::arbitrage::{match_cross, ArbitrageOpportunity};
+use crate::fees::FeeConfig;
+use crate::kalshi::KalshiMarket;
+use crate::polymarket::PolymarketMarket;
+
+/// A single price level normalized to dollars and contract size.
+#[derive(Debug, Clone)]
+pub struct NormalizedLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// A venue-agnostic book: bids sorted richest-first, asks cheapest-first,
+/// every price already expressed in dollars (0-1 for prediction markets).
+#[derive(Debug, Clone)]
+pub struct NormalizedBook {
+    pub venue: String,
+    pub market_id: String,
+    pub bids: Vec<NormalizedLevel>,
+    pub asks: Vec<NormalizedLevel>,
+}
+
+/// A tradeable venue. Implementing this single trait is all it takes to bring a
+/// new prediction/crypto venue into the arbitrage engine; the normalization of
+/// each venue's bespoke book format happens behind `best_book`.
+#[async_trait]
+pub trait Exchange: Send + Sync {
+    /// Human-readable venue name used in opportunity reports.
+    fn name(&self) -> &str;
+
+    /// Trading fee in cents for `contracts` filled at `price` dollars.
+    fn fee_cents(&self, contracts: f64, price: f64) -> f64;
+
+    /// Resolve a user-supplied identifier (slug / event ticker) to the venue's
+    /// canonical market id, optionally anchored to a named outcome.
+    async fn resolve_market(&self, market_id: &str, anchor: Option<&str>) -> Result<String>;
+
+    /// Fetch the current best book for `market_id`.
+    async fn best_book(&self, market_id: &str) -> Result<NormalizedBook>;
+}
+
+/// Kalshi adapter wrapping the latest market delivered by the Kalshi feed. The
+/// loop that owns the feed refreshes `market`; normalization happens here.
+pub struct KalshiExchange {
+    pub market: KalshiMarket,
+    pub fees: FeeConfig,
+}
+
+#[async_trait]
+impl Exchange for KalshiExchange {
+    fn name(&self) -> &str {
+        "Kalshi"
+    }
+
+    fn fee_cents(&self, contracts: f64, price: f64) -> f64 {
+        self.fees.kalshi_fee_cents(contracts, price)
+    }
+
+    async fn resolve_market(&self, market_id: &str, _anchor: Option<&str>) -> Result<String> {
+        // Returns `market_id` unchanged: event tickers are not resolved here.
+        // Ticker resolution is a startup concern handled by `KalshiClient`; by
+        // the time the engine runs the id is already canonical.
+        Ok(market_id.to_string())
+    }
+
+    async fn best_book(&self, _market_id: &str) -> Result<NormalizedBook> {
+        Ok(normalize_kalshi(&self.market))
+    }
+}
+
+/// Polymarket is push-based, so its adapter serves the latest book delivered by
+/// the streaming client rather than polling inside `best_book`.
+pub struct PolymarketExchange {
+    pub asset_id: String,
+    pub latest: PolymarketMarket,
+    pub fees: FeeConfig,
+}
+
+#[async_trait]
+impl Exchange for PolymarketExchange {
+    fn name(&self) -> &str {
+        "Polymarket"
+    }
+
+    fn fee_cents(&self, contracts: f64, price: f64) -> f64 {
+        self.fees.polymarket_fee_cents(contracts, price)
+    }
+
+    async fn resolve_market(&self, market_id: &str, _anchor: Option<&str>) -> Result<String> {
+        // The anchor outcome is derived from the slug itself.
+        let (asset_id, _anchor) =
+            crate::polymarket::get_asset_id_and_anchor(market_id, false, false).await?;
+        Ok(asset_id)
+    }
+
+    async fn best_book(&self, _market_id: &str) -> Result<NormalizedBook> {
+        Ok(normalize_polymarket(&self.asset_id, &self.latest))
+    }
+}
+
+fn normalize_kalshi(market: &KalshiMarket) -> NormalizedBook {
+    let (mut bids, mut asks) = (Vec::new(), Vec::new());
+    if let Some(ob) = &market.orderbook {
+        bids = ob
+            .yes
+            .iter()
+            .map(|(p, s)| NormalizedLevel { price: *p as f64 / 100.0, size: *s as f64 })
+            .collect();
+        // A yes ask is priced at `100 - no_bid`.
+        asks = ob
+            .no
+            .iter()
+            .map(|(p, s)| NormalizedLevel { price: (100 - *p) as f64 / 100.0, size: *s as f64 })
+            .collect();
+    }
+    bids.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap_or(std::cmp::Ordering::Equal));
+    asks.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap_or(std::cmp::Ordering::Equal));
+    NormalizedBook { venue: "Kalshi".to_string(), market_id: market.ticker.clone(), bids, asks }
+}
+
+fn normalize_polymarket(asset_id: &str, market: &PolymarketMarket) -> NormalizedBook {
+    let level = |l: &crate::polymarket::OrderbookLevel| -> Option<NormalizedLevel> {
+        Some(NormalizedLevel {
+            price: l.price.parse::<f64>().ok()?,
+            size: l.size.parse::<f64>().ok()?,
+        })
+    };
+    let mut bids: Vec<NormalizedLevel> = market.bids.iter().filter_map(level).collect();
+    let mut asks: Vec<NormalizedLevel> = market.asks.iter().filter_map(level).collect();
+    bids.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap_or(std::cmp::Ordering::Equal));
+    asks.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap_or(std::cmp::Ordering::Equal));
+    NormalizedBook { venue: "Polymarket".to_string(), market_id: asset_id.to_string(), bids, asks }
+}
+
+/// Walk the buy venue's asks against the sell venue's bids via the shared
+/// [`match_cross`] matcher, projecting the normalized books into `(price, size)`
+/// slices and charging each leg through the venue's own fee schedule.
+fn cross(
+    buy: &NormalizedBook,
+    buy_fee: &dyn Fn(f64, f64) -> f64,
+    sell: &NormalizedBook,
+    sell_fee: &dyn Fn(f64, f64) -> f64,
+    depth: usize,
+) -> Option<ArbitrageOpportunity> {
+    let buy_asks: Vec<(f64, f64)> = buy.asks.iter().map(|l| (l.price, l.size)).collect();
+    let sell_bids: Vec<(f64, f64)> = sell.bids.iter().map(|l| (l.price, l.size)).collect();
+    match_cross(&buy_asks, &sell_bids, &buy.venue, &sell.venue, depth, buy_fee, sell_fee)
+}
+
+/// Detect arbitrage across every ordered venue pair. `market_ids[i]` is the
+/// already-resolved market id on `exchanges[i]`. Returns every net-positive
+/// opportunity, richest first.
+pub async fn detect_opportunities(
+    exchanges: &[Box<dyn Exchange>],
+    market_ids: &[String],
+    depth: usize,
+) -> Vec<ArbitrageOpportunity> {
+    let mut books = Vec::with_capacity(exchanges.len());
+    for (ex, id) in exchanges.iter().zip(market_ids) {
+        match ex.best_book(id).await {
+            Ok(book) => books.push(Some(book)),
+            Err(_) => books.push(None),
+        }
+    }
+
+    let mut opps = Vec::new();
+    for (i, buy) in exchanges.iter().enumerate() {
+        for (j, sell) in exchanges.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let (Some(buy_book), Some(sell_book)) = (&books[i], &books[j]) else {
+                continue;
+            };
+            if let Some(opp) = cross(
+                buy_book,
+                &|c, p| buy.fee_cents(c, p),
+                sell_book,
+                &|c, p| sell.fee_cents(c, p),
+                depth,
+            ) {
+                opps.push(opp);
+            }
+        }
+    }
+
+    opps.sort_by(|a, b| b.net_profit.partial_cmp(&a.net_profit).unwrap_or(std::cmp::Ordering::Equal));
+    opps
+}
@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::arbitrage::ArbitrageOpportunity;
+use crate::kalshi::{KalshiClient, OrderRequest, OrderStatus};
+
+/// A single submitted leg, tracked so a partial fill can be polled or unwound
+/// and so the caller can record it as a fill against the local account.
+#[derive(Debug, Clone)]
+pub struct SubmittedOrder {
+    pub platform: String,
+    pub market: String,
+    pub order_id: String,
+    /// `true` for a buy, `false` for a sell.
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Pairs a detected opportunity into two executable legs. Defaults to a dry run
+/// that only logs intended orders; set `live` (via `EXECUTE=true`/`--live`) to
+/// actually submit.
+pub struct ExecutionEngine<'a> {
+    kalshi: &'a KalshiClient,
+    clob: reqwest::Client,
+    clob_url: String,
+    live: bool,
+    pub submitted: Vec<SubmittedOrder>,
+}
+
+impl<'a> ExecutionEngine<'a> {
+    pub fn new(kalshi: &'a KalshiClient, clob_url: String, live: bool) -> Self {
+        Self {
+            kalshi,
+            clob: reqwest::Client::new(),
+            clob_url,
+            live,
+            submitted: Vec::new(),
+        }
+    }
+
+    /// Execute `opp`, sizing each leg to the executable volume the detector
+    /// found. Re-checks net profitability (fees already folded in) before
+    /// firing, and in dry-run mode only logs the intended orders.
+    pub async fn execute(
+        &mut self,
+        opp: &ArbitrageOpportunity,
+        kalshi_ticker: &str,
+        poly_token_id: &str,
+    ) -> Result<()> {
+        // Guard against acting on a cross that is not net-positive after fees.
+        if opp.net_profit <= 0.0 {
+            return Ok(());
+        }
+
+        let size = opp.max_size.floor();
+        if size < 1.0 {
+            return Ok(());
+        }
+
+        let (kalshi_price, poly_price, kalshi_is_buy) = if opp.buy_platform == "Kalshi" {
+            (opp.buy_price, opp.sell_price, true)
+        } else {
+            (opp.sell_price, opp.buy_price, false)
+        };
+
+        if !self.live {
+            println!(
+                "{}",
+                format!(
+                    "[dry-run] would {} {:.0} Kalshi @ ${:.2} and {} {:.0} Polymarket @ ${:.4} (net {:.2}¢)",
+                    if kalshi_is_buy { "BUY" } else { "SELL" },
+                    size,
+                    kalshi_price,
+                    if kalshi_is_buy { "SELL" } else { "BUY" },
+                    size,
+                    poly_price,
+                    opp.net_profit
+                )
+                .dimmed()
+            );
+            return Ok(());
+        }
+
+        // Kalshi leg: a yes buy/sell at the crossing price.
+        let order = OrderRequest {
+            ticker: kalshi_ticker.to_string(),
+            side: "yes".to_string(),
+            action: if kalshi_is_buy { "buy" } else { "sell" }.to_string(),
+            count: size as i32,
+            yes_price: (kalshi_price * 100.0).round() as i32,
+            order_type: "limit".to_string(),
+            client_order_id: format!("arb-{}-{}", kalshi_ticker, opp.max_size as u64),
+        };
+        let kalshi_status = self.kalshi.place_order(&order).await?;
+        self.submitted.push(SubmittedOrder {
+            platform: "Kalshi".to_string(),
+            market: kalshi_ticker.to_string(),
+            order_id: kalshi_status.order_id.clone(),
+            is_buy: kalshi_is_buy,
+            price: kalshi_price,
+            size,
+        });
+
+        // Polymarket leg on the opposite side.
+        let poly_id = self
+            .submit_polymarket(poly_token_id, poly_price, size, !kalshi_is_buy)
+            .await?;
+        self.submitted.push(SubmittedOrder {
+            platform: "Polymarket".to_string(),
+            market: poly_token_id.to_string(),
+            order_id: poly_id,
+            is_buy: !kalshi_is_buy,
+            price: poly_price,
+            size,
+        });
+
+        println!("{}", "✓ Paired orders submitted".green().bold());
+        Ok(())
+    }
+
+    /// Place a single resting quote on either venue, reusing the signed Kalshi
+    /// and Polymarket CLOB order paths. Returns the assigned order id.
+    pub async fn place_quote(
+        &mut self,
+        platform: &str,
+        market: &str,
+        is_buy: bool,
+        price: f64,
+        size: f64,
+    ) -> Result<String> {
+        if !self.live {
+            println!(
+                "{}",
+                format!(
+                    "[dry-run] quote {} {:.0} {} @ ${:.4}",
+                    if is_buy { "BUY" } else { "SELL" },
+                    size,
+                    platform,
+                    price
+                )
+                .dimmed()
+            );
+            return Ok(format!("dry-{}-{}", platform, (price * 10000.0) as u64));
+        }
+
+        match platform {
+            "Kalshi" => {
+                let order = OrderRequest {
+                    ticker: market.to_string(),
+                    side: "yes".to_string(),
+                    action: if is_buy { "buy" } else { "sell" }.to_string(),
+                    count: size as i32,
+                    yes_price: (price * 100.0).round() as i32,
+                    order_type: "limit".to_string(),
+                    client_order_id: format!("mm-{}-{}", market, (price * 10000.0) as u64),
+                };
+                Ok(self.kalshi.place_order(&order).await?.order_id)
+            }
+            _ => self.submit_polymarket(market, price, size, is_buy).await,
+        }
+    }
+
+    /// Place a CLOB order on Polymarket, returning the assigned order id.
+    async fn submit_polymarket(&self, token_id: &str, price: f64, size: f64, is_buy: bool) -> Result<String> {
+        let payload = serde_json::json!({
+            "tokenID": token_id,
+            "price": price,
+            "size": size,
+            "side": if is_buy { "BUY" } else { "SELL" },
+        });
+        let response = self
+            .clob
+            .post(format!("{}/order", self.clob_url))
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to submit Polymarket order")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Polymarket order rejected: {}", response.status());
+        }
+        #[derive(serde::Deserialize)]
+        struct ClobResponse {
+            #[serde(rename = "orderID")]
+            order_id: String,
+        }
+        let parsed: ClobResponse = response.json().await.context("Failed to parse CLOB response")?;
+        Ok(parsed.order_id)
+    }
+
+    /// Poll the status of the Kalshi leg of a submitted pair.
+    pub async fn kalshi_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        self.kalshi.get_order(order_id).await
+    }
+
+    /// Cancel the Kalshi leg so a partially-filled pair can be unwound.
+    pub async fn cancel_kalshi(&self, order_id: &str) -> Result<()> {
+        if !self.live {
+            println!("{}", format!("[dry-run] cancel Kalshi order {}", order_id).dimmed());
+            return Ok(());
+        }
+        self.kalshi.cancel_order(order_id).await
+    }
+}
@@ -0,0 +1,28 @@
+/// Trading fee parameters for each venue. Different fee tiers can be supplied
+/// so the arbitrage path reports net rather than gross profit.
+#[derive(Debug, Clone)]
+pub struct FeeConfig {
+    /// Polymarket proportional fee (fraction of notional, per leg). Currently
+    /// zero on the live venue but configurable for fee-bearing tiers.
+    pub polymarket_rate: f64,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self { polymarket_rate: 0.0 }
+    }
+}
+
+impl FeeConfig {
+    /// Kalshi's per-order trading fee in cents: `ceil(0.07 * C * P * (1 - P) *
+    /// 100)`, i.e. the `0.07 * C * P * (1 - P)` dollar fee scaled to whole cents,
+    /// where `C` is the contract count and `P` the execution price in dollars.
+    pub fn kalshi_fee_cents(&self, contracts: f64, price: f64) -> f64 {
+        (0.07 * contracts * price * (1.0 - price) * 100.0).ceil()
+    }
+
+    /// Polymarket fee in cents for `contracts` filled at `price` dollars.
+    pub fn polymarket_fee_cents(&self, contracts: f64, price: f64) -> f64 {
+        self.polymarket_rate * contracts * price * 100.0
+    }
+}
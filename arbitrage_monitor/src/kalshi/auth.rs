@@ -34,18 +34,32 @@ pub fn generate_signature(
     method: &str,
     path: &str,
 ) -> Result<String> {
-    // Create message: timestamp + method + path
-    let message = format!("{}{}{}", timestamp, method, path);
-    
+    sign_message(private_key, &format!("{}{}{}", timestamp, method, path))
+}
+
+/// Generate an RSA-PSS signature covering the request body, for signed writes
+/// (`POST`/`DELETE`). The signed message is `timestamp + method + path + body`.
+pub fn generate_signature_with_body(
+    private_key: &RsaPrivateKey,
+    timestamp: u128,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> Result<String> {
+    sign_message(private_key, &format!("{}{}{}{}", timestamp, method, path, body))
+}
+
+/// Sign `message` with RSA-PSS/SHA256 and return the base64 signature.
+fn sign_message(private_key: &RsaPrivateKey, message: &str) -> Result<String> {
     // Create PSS signing key with SHA256
     let mut rng = rand::thread_rng();
     let signing_key = BlindedSigningKey::<Sha256>::new(private_key.clone());
-    
+
     // Sign the message
     let signature: Signature = signing_key.sign_with_rng(&mut rng, message.as_bytes());
-    
+
     // Base64 encode
     let signature_b64 = general_purpose::STANDARD.encode(signature.to_bytes());
-    
+
     Ok(signature_b64)
 }
@@ -1,11 +1,112 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
+use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
 use reqwest;
 use rsa::RsaPrivateKey;
 use serde::Deserialize;
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+
+use super::auth::{generate_signature, generate_signature_with_body};
+
+/// A Kalshi order to be placed via `POST /trade-api/v2/portfolio/orders`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OrderRequest {
+    pub ticker: String,
+    /// `"yes"` or `"no"`.
+    pub side: String,
+    /// `"buy"` or `"sell"`.
+    pub action: String,
+    /// Contract count.
+    pub count: i32,
+    /// Limit price in cents.
+    pub yes_price: i32,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    pub client_order_id: String,
+}
+
+/// The order payload Kalshi echoes back on submission / status.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct OrderStatus {
+    pub order_id: String,
+    pub status: String,
+    #[serde(default)]
+    pub remaining_count: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderEnvelope {
+    order: OrderStatus,
+}
+
+/// Structured Kalshi API error parsed from the JSON error body
+/// (`{ "error": { "code", "message" } }`), so callers can distinguish a rate
+/// limit from an auth failure or a transient server error.
+#[derive(Debug, Clone)]
+pub enum KalshiError {
+    /// HTTP 429. Carries the server-requested wait, when supplied.
+    RateLimited { retry_after: Option<Duration>, message: String },
+    /// HTTP 401/403.
+    Unauthorized { message: String },
+    /// HTTP 404.
+    NotFound { message: String },
+    /// HTTP 5xx.
+    Server { status: u16, message: String },
+    /// Any other non-success status, carrying Kalshi's error code.
+    Api { status: u16, code: String, message: String },
+    /// Transport-level failure (connection, timeout, body read).
+    Transport { message: String },
+}
+
+impl KalshiError {
+    /// Whether the request is worth retrying after a backoff.
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            KalshiError::RateLimited { .. } | KalshiError::Server { .. } | KalshiError::Transport { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for KalshiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KalshiError::RateLimited { message, .. } => write!(f, "rate limited: {}", message),
+            KalshiError::Unauthorized { message } => write!(f, "unauthorized: {}", message),
+            KalshiError::NotFound { message } => write!(f, "not found: {}", message),
+            KalshiError::Server { status, message } => write!(f, "server error {}: {}", status, message),
+            KalshiError::Api { status, code, message } => {
+                write!(f, "Kalshi API error {} [{}]: {}", status, code, message)
+            }
+            KalshiError::Transport { message } => write!(f, "transport error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for KalshiError {}
+
+/// Kalshi's JSON error envelope.
+#[derive(Debug, Deserialize)]
+struct ErrorEnvelope {
+    error: Option<ErrorBody>,
+}
 
-use super::auth::{generate_signature};
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    message: String,
+}
 
 /// Kalshi market data
 #[derive(Debug, Deserialize, Clone)]
@@ -43,6 +144,8 @@ pub struct KalshiClient {
     private_key: RsaPrivateKey,
     base_url: String,
     debug: bool,
+    /// Maximum number of retries on rate-limit / transient server errors.
+    max_retries: u32,
 }
 
 impl KalshiClient {
@@ -53,6 +156,7 @@ impl KalshiClient {
             private_key,
             base_url: "https://api.elections.kalshi.com".to_string(),
             debug: false,
+            max_retries: 5,
         }
     }
 
@@ -60,39 +164,121 @@ impl KalshiClient {
         self.debug = debug;
     }
 
-    /// Fetch market data for a given ticker
-    pub async fn get_market(&self, ticker: &str) -> Result<KalshiMarket> {
-        // Generate timestamp
+    /// Override how many times a throttled or transient request is retried.
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    /// Build an event-driven WebSocket client for `ticker`, sharing this
+    /// client's credentials. Prefer this over polling `get_market` so the
+    /// arbitrage loop reacts to every book change instead of timing a REST
+    /// poll.
+    pub fn ws_client(&self, ticker: &str) -> KalshiWsClient {
+        let ws_url = self
+            .base_url
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1)
+            + "/trade-api/ws/v2";
+        KalshiWsClient {
+            ws_url,
+            api_key: self.api_key.clone(),
+            private_key: self.private_key.clone(),
+            ticker: ticker.to_string(),
+            ws_stream: None,
+            orderbook: KalshiOrderbook { yes: Vec::new(), no: Vec::new() },
+            seq: 0,
+            next_id: 1,
+            debug: self.debug,
+        }
+    }
+
+    /// Perform a signed GET against `path`, retrying on `RateLimited`/`Server`/
+    /// `Transport` with exponential backoff + jitter (honoring `Retry-After`) up to
+    /// `max_retries`. Returns the response body text on success.
+    async fn signed_get(&self, path: &str, query: &[(&str, &str)]) -> Result<String, KalshiError> {
+        let mut attempt = 0u32;
+        loop {
+            match self.signed_get_once(path, query).await {
+                Ok(body) => return Ok(body),
+                Err(e) if e.is_retryable() && attempt < self.max_retries => {
+                    let wait = self.backoff(&e, attempt);
+                    if self.debug {
+                        println!(
+                            "{} {} (attempt {}/{}), backing off {:?}",
+                            "[DEBUG] Kalshi retry:".yellow().bold(),
+                            e,
+                            attempt + 1,
+                            self.max_retries,
+                            wait
+                        );
+                    }
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// A single signed GET attempt, mapping the HTTP outcome onto `KalshiError`.
+    async fn signed_get_once(&self, path: &str, query: &[(&str, &str)]) -> Result<String, KalshiError> {
         let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)?
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| KalshiError::Transport { message: e.to_string() })?
             .as_millis();
 
-        // Generate signature
-        let path = format!("/trade-api/v2/markets/{}", ticker);
-        let signature = generate_signature(&self.private_key, timestamp, "GET", &path)?;
+        let signature = generate_signature(&self.private_key, timestamp, "GET", path)
+            .map_err(|e| KalshiError::Transport { message: e.to_string() })?;
 
-        // Make request
         let url = format!("{}{}", self.base_url, path);
-        
-        let response = self.client
+        let response = self
+            .client
             .get(&url)
             .header("KALSHI-ACCESS-KEY", &self.api_key)
             .header("KALSHI-ACCESS-TIMESTAMP", timestamp.to_string())
             .header("KALSHI-ACCESS-SIGNATURE", signature)
+            .query(query)
             .send()
             .await
-            .context("Failed to send request")?;
+            .map_err(|e| KalshiError::Transport { message: e.to_string() })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return response
+                .text()
+                .await
+                .map_err(|e| KalshiError::Transport { message: e.to_string() });
+        }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!("Kalshi API error {}: {}", status, text));
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await.unwrap_or_default();
+        Err(classify_error(status.as_u16(), &body, retry_after))
+    }
+
+    /// Exponential backoff plus up to the same amount again in random jitter,
+    /// capped at 30s; a `Retry-After`
+    /// hint takes precedence when the server supplied one.
+    fn backoff(&self, error: &KalshiError, attempt: u32) -> Duration {
+        if let KalshiError::RateLimited { retry_after: Some(d), .. } = error {
+            return *d;
         }
+        let base = 2u64.saturating_pow(attempt).min(32) as f64 * 0.25;
+        let jitter = rand::thread_rng().gen_range(0.0..base.max(0.001));
+        Duration::from_secs_f64((base + jitter).min(30.0))
+    }
 
-        let market_response: MarketResponse = response
-            .json()
-            .await
-            .context("Failed to parse Kalshi response")?;
+    /// Fetch market data for a given ticker
+    pub async fn get_market(&self, ticker: &str) -> Result<KalshiMarket> {
+        let path = format!("/trade-api/v2/markets/{}", ticker);
+        let body = self.signed_get(&path, &[]).await?;
+
+        let market_response: MarketResponse =
+            serde_json::from_str(&body).context("Failed to parse Kalshi response")?;
 
         let mut market = market_response.market;
         
@@ -106,32 +292,9 @@ impl KalshiClient {
 
     /// Fetch orderbook for a given ticker
     pub async fn get_orderbook(&self, ticker: &str) -> Result<KalshiOrderbook> {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)?
-            .as_millis();
-
         let path = format!("/trade-api/v2/markets/{}/orderbook", ticker);
-        let signature = generate_signature(&self.private_key, timestamp, "GET", &path)?;
+        let body = self.signed_get(&path, &[]).await?;
 
-        let url = format!("{}{}", self.base_url, path);
-        
-        let response = self.client
-            .get(&url)
-            .header("KALSHI-ACCESS-KEY", &self.api_key)
-            .header("KALSHI-ACCESS-TIMESTAMP", timestamp.to_string())
-            .header("KALSHI-ACCESS-SIGNATURE", signature)
-            .send()
-            .await
-            .context("Failed to send orderbook request")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!("Kalshi API error {}: {}", status, text));
-        }
-
-        let body = response.text().await.context("Failed to read Kalshi orderbook body")?;
-        
         if self.debug {
             println!("\n{} {}", "[DEBUG] Kalshi Raw Orderbook:".yellow().bold(), body);
         }
@@ -149,39 +312,192 @@ impl KalshiClient {
 
 
 
-    /// Resolve an event ticker to a specific market ticker
-    pub async fn resolve_market_ticker(&self, event_ticker: &str, target_team: Option<&str>) -> Result<String> {
+    /// Submit a signed order to the portfolio endpoint. The RSA-PSS signature
+    /// covers the serialized request body in addition to the path.
+    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderStatus> {
+        let body = serde_json::to_string(order).context("Failed to serialize order")?;
+        let body = self
+            .signed_write("POST", "/trade-api/v2/portfolio/orders", &body)
+            .await?;
+        let envelope: OrderEnvelope =
+            serde_json::from_str(&body).context("Failed to parse order response")?;
+        Ok(envelope.order)
+    }
+
+    /// Poll the status of a previously submitted order.
+    pub async fn get_order(&self, order_id: &str) -> Result<OrderStatus> {
+        let body = self
+            .signed_get(&format!("/trade-api/v2/portfolio/orders/{}", order_id), &[])
+            .await?;
+        let envelope: OrderEnvelope =
+            serde_json::from_str(&body).context("Failed to parse order status")?;
+        Ok(envelope.order)
+    }
+
+    /// Cancel a resting order so a partially-filled leg can be unwound.
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        self.signed_write("DELETE", &format!("/trade-api/v2/portfolio/orders/{}", order_id), "")
+            .await?;
+        Ok(())
+    }
+
+    /// Perform a signed write (`POST`/`DELETE`) whose signature covers the body.
+    async fn signed_write(&self, method: &str, path: &str, body: &str) -> Result<String, KalshiError> {
         let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)?
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| KalshiError::Transport { message: e.to_string() })?
             .as_millis();
-
-        let path = "/trade-api/v2/markets";
-        let signature = generate_signature(&self.private_key, timestamp, "GET", path)?;
+        let signature = generate_signature_with_body(&self.private_key, timestamp, method, path, body)
+            .map_err(|e| KalshiError::Transport { message: e.to_string() })?;
 
         let url = format!("{}{}", self.base_url, path);
-        
-        let event_ticker_upper = event_ticker.to_uppercase();
-        
-        let response = self.client
-            .get(&url)
+        let builder = self
+            .client
+            .request(
+                method.parse().map_err(|_| KalshiError::Transport {
+                    message: format!("invalid method {}", method),
+                })?,
+                &url,
+            )
             .header("KALSHI-ACCESS-KEY", &self.api_key)
             .header("KALSHI-ACCESS-TIMESTAMP", timestamp.to_string())
-            .header("KALSHI-ACCESS-SIGNATURE", signature)
-            .query(&[("event_ticker", &event_ticker_upper)])
+            .header("KALSHI-ACCESS-SIGNATURE", signature);
+        let builder = if body.is_empty() {
+            builder
+        } else {
+            builder.header("Content-Type", "application/json").body(body.to_string())
+        };
+
+        let response = builder
             .send()
             .await
-            .context("Failed to fetch markets for event")?;
+            .map_err(|e| KalshiError::Transport { message: e.to_string() })?;
+        let status = response.status();
+        if status.is_success() {
+            return response
+                .text()
+                .await
+                .map_err(|e| KalshiError::Transport { message: e.to_string() });
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(classify_error(status.as_u16(), &body, None))
+    }
+
+    /// Fetch the account's available balance in cents.
+    pub async fn get_balance(&self) -> Result<i64> {
+        let body = self.signed_get("/trade-api/v2/portfolio/balance", &[]).await?;
+        #[derive(Deserialize)]
+        struct BalanceResponse {
+            balance: i64,
+        }
+        let parsed: BalanceResponse =
+            serde_json::from_str(&body).context("Failed to parse Kalshi balance")?;
+        Ok(parsed.balance)
+    }
 
+    /// Fetch the account's current market positions.
+    pub async fn get_positions(&self) -> Result<Vec<KalshiPosition>> {
+        let body = self.signed_get("/trade-api/v2/portfolio/positions", &[]).await?;
+        #[derive(Deserialize)]
+        struct PositionsResponse {
+            #[serde(default)]
+            market_positions: Vec<KalshiPosition>,
+        }
+        let parsed: PositionsResponse =
+            serde_json::from_str(&body).context("Failed to parse Kalshi positions")?;
+        Ok(parsed.market_positions)
+    }
+
+    /// Verify connectivity to the exchange. Hits the public status endpoint and
+    /// returns `Ok(())` when the exchange reports the trading API as reachable.
+    pub async fn ping(&self) -> Result<()> {
+        let url = format!("{}/trade-api/v2/exchange/status", self.base_url);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to reach Kalshi exchange status")?;
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!("Kalshi API error {}: {}", status, text));
+            anyhow::bail!("Kalshi exchange status returned {}", response.status());
         }
+        Ok(())
+    }
 
-        let markets_response: MarketsResponse = response
-            .json()
+    /// Read the exchange's current time (epoch millis) from the `Date` response
+    /// header on the status endpoint.
+    pub async fn server_time(&self) -> Result<u128> {
+        let url = format!("{}/trade-api/v2/exchange/status", self.base_url);
+        let response = self
+            .client
+            .get(&url)
+            .send()
             .await
-            .context("Failed to parse Kalshi response")?;
+            .context("Failed to fetch Kalshi server time")?;
+        let date = response
+            .headers()
+            .get("date")
+            .and_then(|v| v.to_str().ok())
+            .context("Kalshi response missing Date header")?;
+        let parsed = chrono::DateTime::parse_from_rfc2822(date)
+            .context("Failed to parse Kalshi Date header")?;
+        Ok(parsed.timestamp_millis() as u128)
+    }
+
+    /// Compute local-vs-server clock drift in milliseconds and warn when it
+    /// exceeds `tolerance_ms`, since RSA-PSS signatures embed a millisecond
+    /// timestamp that Kalshi rejects once it drifts too far.
+    pub async fn check_clock_skew(&self, tolerance_ms: i128) -> Result<i128> {
+        let server = self.server_time().await? as i128;
+        let local = SystemTime::now()
+            .duration_since(UNIX_EPOCH)?
+            .as_millis() as i128;
+        let skew = local - server;
+        if skew.abs() > tolerance_ms {
+            println!(
+                "{} local clock differs from Kalshi by {}ms; signatures may be rejected",
+                "⚠️  Clock skew:".yellow().bold(),
+                skew
+            );
+        }
+        Ok(skew)
+    }
+
+    /// List tradeable markets, paginating through Kalshi's `cursor` until the
+    /// exchange stops returning one. Use this to discover arbitrage candidates
+    /// rather than hand-specifying tickers.
+    pub async fn list_markets(&self) -> Result<Vec<MarketInfo>> {
+        let mut markets = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut query: Vec<(&str, &str)> = vec![("limit", "1000")];
+            if let Some(c) = &cursor {
+                query.push(("cursor", c));
+            }
+            let body = self.signed_get("/trade-api/v2/markets", &query).await?;
+            let page: MarketsResponse =
+                serde_json::from_str(&body).context("Failed to parse Kalshi markets page")?;
+
+            markets.extend(page.markets);
+            match page.cursor {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => break,
+            }
+        }
+        Ok(markets)
+    }
+
+    /// Resolve an event ticker to a specific market ticker
+    pub async fn resolve_market_ticker(&self, event_ticker: &str, target_team: Option<&str>) -> Result<String> {
+        let path = "/trade-api/v2/markets";
+        let event_ticker_upper = event_ticker.to_uppercase();
+
+        let body = self
+            .signed_get(path, &[("event_ticker", &event_ticker_upper)])
+            .await?;
+
+        let markets_response: MarketsResponse =
+            serde_json::from_str(&body).context("Failed to parse Kalshi response")?;
 
         if markets_response.markets.is_empty() {
             anyhow::bail!("No markets found for event ticker: {}", event_ticker);
@@ -225,6 +541,27 @@ impl KalshiClient {
     }
 }
 
+/// Map an HTTP status + error body onto a typed [`KalshiError`].
+fn classify_error(status: u16, body: &str, retry_after: Option<Duration>) -> KalshiError {
+    let parsed = serde_json::from_str::<ErrorEnvelope>(body)
+        .ok()
+        .and_then(|e| e.error);
+    let code = parsed.as_ref().map(|e| e.code.clone()).unwrap_or_default();
+    let message = parsed
+        .as_ref()
+        .map(|e| e.message.clone())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| body.to_string());
+
+    match status {
+        429 => KalshiError::RateLimited { retry_after, message },
+        401 | 403 => KalshiError::Unauthorized { message },
+        404 => KalshiError::NotFound { message },
+        500..=599 => KalshiError::Server { status, message },
+        _ => KalshiError::Api { status, code, message },
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 struct MarketsResponse {
@@ -237,3 +574,193 @@ pub struct MarketInfo {
     pub ticker: String,
     pub title: Option<String>,
 }
+
+/// A resting Kalshi position returned by the portfolio endpoint.
+#[derive(Debug, Deserialize, Clone)]
+#[allow(dead_code)]
+pub struct KalshiPosition {
+    pub ticker: String,
+    /// Signed contract count: positive = long yes, negative = short.
+    pub position: i32,
+    /// Market exposure (cost of the position) in cents.
+    #[serde(default)]
+    pub market_exposure: i64,
+}
+
+/// Event-driven Kalshi order book feed. Authenticates once, subscribes to the
+/// `orderbook_snapshot`/`orderbook_delta` channel for a single ticker, and
+/// maintains a local book so the main loop can select on the stream instead of
+/// timing a REST poll.
+pub struct KalshiWsClient {
+    ws_url: String,
+    api_key: String,
+    private_key: RsaPrivateKey,
+    ticker: String,
+    ws_stream: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    orderbook: KalshiOrderbook,
+    seq: u64,
+    next_id: i64,
+    debug: bool,
+}
+
+impl KalshiWsClient {
+    /// Connect, authenticate with a signed handshake, and subscribe to the
+    /// order book channel for the configured ticker.
+    pub async fn connect(&mut self) -> Result<()> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        // The WS handshake is signed over the upgrade path, as for REST GETs.
+        let signature = generate_signature(&self.private_key, timestamp, "GET", "/trade-api/ws/v2")?;
+
+        let mut request = self
+            .ws_url
+            .as_str()
+            .into_client_request()
+            .context("Invalid Kalshi WebSocket URL")?;
+        let headers = request.headers_mut();
+        headers.insert("KALSHI-ACCESS-KEY", self.api_key.parse()?);
+        headers.insert("KALSHI-ACCESS-TIMESTAMP", timestamp.to_string().parse()?);
+        headers.insert("KALSHI-ACCESS-SIGNATURE", signature.parse()?);
+
+        let (mut ws_stream, _) = connect_async(request)
+            .await
+            .context("Failed to connect to Kalshi WebSocket")?;
+
+        let subscribe = json!({
+            "id": self.next_id,
+            "cmd": "subscribe",
+            "params": {
+                "channels": ["orderbook_delta"],
+                "market_tickers": [self.ticker],
+            }
+        });
+        self.next_id += 1;
+        ws_stream
+            .send(Message::Text(subscribe.to_string()))
+            .await
+            .context("Failed to subscribe to Kalshi order book")?;
+
+        self.ws_stream = Some(ws_stream);
+        self.seq = 0;
+        Ok(())
+    }
+
+    /// Read the next book update, applying it to the local book and returning a
+    /// [`KalshiMarket`] in the same shape the display and arbitrage code expect.
+    /// A sequence gap triggers a resubscribe and a fresh snapshot.
+    pub async fn read_next_market(&mut self) -> Result<KalshiMarket> {
+        loop {
+            let stream = self
+                .ws_stream
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("Not connected. Call connect() first"))?;
+
+            let Some(msg) = stream.next().await else {
+                return Err(anyhow::anyhow!("Kalshi WebSocket closed"));
+            };
+
+            let text = match msg {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return Err(anyhow::anyhow!("Kalshi WebSocket closed")),
+                Ok(_) => continue,
+                Err(e) => return Err(anyhow::anyhow!("Kalshi WebSocket error: {}", e)),
+            };
+
+            if self.debug {
+                println!("\n{} {}", "[DEBUG] Kalshi WS Message:".yellow().bold(), text);
+            }
+
+            let data: serde_json::Value = match serde_json::from_str(&text) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            match data["type"].as_str() {
+                Some("orderbook_snapshot") => {
+                    self.apply_snapshot(&data["msg"]);
+                    self.seq = data["seq"].as_u64().unwrap_or(0);
+                    return Ok(self.to_market());
+                }
+                Some("orderbook_delta") => {
+                    let seq = data["seq"].as_u64().unwrap_or(0);
+                    if self.seq != 0 && seq != self.seq + 1 {
+                        // Gap detected: resubscribe for a fresh snapshot.
+                        if self.debug {
+                            println!(
+                                "{} seq gap (have {}, got {}); resubscribing",
+                                "[DEBUG] Kalshi WS:".yellow().bold(),
+                                self.seq,
+                                seq
+                            );
+                        }
+                        self.connect().await?;
+                        continue;
+                    }
+                    self.apply_delta(&data["msg"]);
+                    self.seq = seq;
+                    return Ok(self.to_market());
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn apply_snapshot(&mut self, msg: &serde_json::Value) {
+        self.orderbook.yes = parse_levels(&msg["yes"]);
+        self.orderbook.no = parse_levels(&msg["no"]);
+    }
+
+    fn apply_delta(&mut self, msg: &serde_json::Value) {
+        let price = msg["price"].as_i64().unwrap_or(0) as i32;
+        let delta = msg["delta"].as_i64().unwrap_or(0) as i32;
+        let side = msg["side"].as_str().unwrap_or("yes");
+        let levels = if side == "no" { &mut self.orderbook.no } else { &mut self.orderbook.yes };
+
+        if let Some(level) = levels.iter_mut().find(|(p, _)| *p == price) {
+            level.1 += delta;
+        } else if delta > 0 {
+            levels.push((price, delta));
+        }
+        // Drop levels whose resting size fell to zero or below.
+        levels.retain(|(_, size)| *size > 0);
+    }
+
+    /// Project the maintained book into a [`KalshiMarket`], deriving top-of-book
+    /// yes bid/ask from the yes and no ladders.
+    fn to_market(&self) -> KalshiMarket {
+        let yes_bid = self.orderbook.yes.iter().map(|(p, _)| *p).max().unwrap_or(0);
+        let yes_ask = self
+            .orderbook
+            .no
+            .iter()
+            .map(|(p, _)| 100 - *p)
+            .min()
+            .unwrap_or(0);
+        KalshiMarket {
+            ticker: self.ticker.clone(),
+            title: self.ticker.clone(),
+            yes_bid,
+            yes_ask,
+            no_bid: self.orderbook.no.iter().map(|(p, _)| *p).max().unwrap_or(0),
+            no_ask: 100 - yes_bid,
+            last_price: 0,
+            volume_24h: 0,
+            open_interest: 0,
+            orderbook: Some(self.orderbook.clone()),
+        }
+    }
+}
+
+/// Parse Kalshi's `[[price, size], ...]` level arrays.
+fn parse_levels(value: &serde_json::Value) -> Vec<(i32, i32)> {
+    value
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|lvl| {
+                    let pair = lvl.as_array()?;
+                    Some((pair.first()?.as_i64()? as i32, pair.get(1)?.as_i64()? as i32))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,6 +1,17 @@
 mod kalshi;
 mod polymarket;
 mod arbitrage;
+mod fees;
+mod exchange;
+mod execution;
+mod making;
+mod scanner;
+mod account;
+mod broadcaster;
+#[cfg(feature = "storage")]
+mod storage;
+#[cfg(feature = "storage")]
+mod candles;
 
 use anyhow::{Context, Result};
 use colored::*;
@@ -10,7 +21,22 @@ use tokio::time::{sleep, Duration};
 
 use kalshi::{KalshiClient, KalshiMarket};
 use polymarket::{PolymarketClient, PolymarketMarket};
-use arbitrage::{detect_arbitrage, ArbitrageOpportunity};
+use arbitrage::ArbitrageOpportunity;
+use exchange::{Exchange, KalshiExchange, PolymarketExchange};
+
+/// Parse a `--depth N` flag from the command line, if present.
+fn parse_depth_flag() -> Option<usize> {
+    let mut args = env::args();
+    while let Some(arg) = args.next() {
+        if arg == "--depth" {
+            return args.next().and_then(|v| v.parse::<usize>().ok());
+        }
+        if let Some(val) = arg.strip_prefix("--depth=") {
+            return val.parse::<usize>().ok();
+        }
+    }
+    None
+}
 
 fn display_markets(target_team: &str, kalshi: &KalshiMarket, polymarket: &PolymarketMarket) {
     let now = chrono::Local::now().format("%H:%M:%S");
@@ -104,10 +130,76 @@ fn display_arbitrage(opp: &ArbitrageOpportunity) {
     println!("{}", format!("Buy on:  {} @ ${:.4}", opp.buy_platform, opp.buy_price).cyan());
     println!("{}", format!("Sell on: {} @ ${:.4}", opp.sell_platform, opp.sell_price).cyan());
     println!();
-    println!("{}", format!("💰 Profit: {:.2}¢ ({:.2}%)", opp.profit_cents, opp.profit_pct).green().bold());
+    println!("{}", format!("💰 Profit: {:.2}¢/unit ({:.2}%)", opp.profit_cents, opp.profit_pct).green().bold());
+    println!("{}", format!("📦 Executable size: {:.0} contracts", opp.max_size).cyan());
+    println!("{}", format!("💵 Gross profit: {:.2}¢", opp.total_profit).cyan());
+    println!("{}", format!("🧾 Fees:         {:.2}¢", opp.fees).dimmed());
+    println!("{}", format!("✅ Net profit:   {:.2}¢", opp.net_profit).green().bold());
     println!("{}", "═".repeat(70).yellow());
 }
 
+/// Project a Kalshi market into a snapshot row: top-of-book in dollars and the
+/// summed resting size across the recorded order-book levels.
+#[cfg(feature = "storage")]
+fn kalshi_snapshot_row(ts_ms: i64, ticker: &str, market: &KalshiMarket) -> storage::SnapshotRow {
+    let best_bid = market.yes_bid as f64 / 100.0;
+    let best_ask = market.yes_ask as f64 / 100.0;
+    let depth = market
+        .orderbook
+        .as_ref()
+        .map(|ob| ob.yes.iter().chain(&ob.no).map(|(_, s)| *s as f64).sum())
+        .unwrap_or(0.0);
+    storage::SnapshotRow {
+        ts_ms,
+        venue: "Kalshi".to_string(),
+        market_id: ticker.to_string(),
+        best_bid,
+        best_ask,
+        depth,
+        spread: best_ask - best_bid,
+    }
+}
+
+/// Project a Polymarket market into a snapshot row, summing the parsed resting
+/// size across both sides of the book.
+#[cfg(feature = "storage")]
+fn polymarket_snapshot_row(ts_ms: i64, asset_id: &str, market: &PolymarketMarket) -> storage::SnapshotRow {
+    let depth: f64 = market
+        .bids
+        .iter()
+        .chain(&market.asks)
+        .filter_map(|l| l.size.parse::<f64>().ok())
+        .sum();
+    storage::SnapshotRow {
+        ts_ms,
+        venue: "Polymarket".to_string(),
+        market_id: asset_id.to_string(),
+        best_bid: market.best_bid,
+        best_ask: market.best_ask,
+        depth,
+        spread: market.best_ask - market.best_bid,
+    }
+}
+
+/// Fetch Kalshi's reported balance and positions for the PnL reconciliation
+/// panel. Returns `None` when either portfolio call fails, so a transient API
+/// hiccup just hides the reconciliation line rather than stalling the loop.
+async fn fetch_kalshi_state(kalshi: &KalshiClient) -> Option<account::VenueState> {
+    let balance = kalshi.get_balance().await.ok()?;
+    let positions = kalshi.get_positions().await.ok()?;
+    Some(account::VenueState {
+        balance: balance as f64 / 100.0,
+        positions: positions
+            .into_iter()
+            .map(|p| account::ReportedPosition {
+                venue: "Kalshi".to_string(),
+                market_id: p.ticker,
+                net_size: p.position as f64,
+            })
+            .collect(),
+    })
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables
@@ -123,6 +215,20 @@ async fn main() -> Result<()> {
         .parse::<f64>()
         .unwrap_or(0.5);
 
+    // Cap how many order book levels the depth-aware detector considers.
+    // `--depth N` takes precedence over the DEPTH env var.
+    let depth = parse_depth_flag()
+        .or_else(|| env::var("DEPTH").ok().and_then(|v| v.parse::<usize>().ok()))
+        .unwrap_or(arbitrage::DEFAULT_DEPTH);
+
+    // Fee model; POLYMARKET_FEE_RATE lets fee-bearing tiers be configured.
+    let fee_config = fees::FeeConfig {
+        polymarket_rate: env::var("POLYMARKET_FEE_RATE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0),
+    };
+
     let verbose = env::var("VERBOSE")
         .unwrap_or_else(|_| "false".to_string())
         .to_lowercase() == "true";
@@ -136,12 +242,14 @@ async fn main() -> Result<()> {
         poll_interval = 30.0;
     }
 
-    // Manual market configuration (can be event-level slugs/tickers)
-    let polymarket_slug = env::var("POLYMARKET_SLUG")
-        .context("POLYMARKET_SLUG not found in .env")?;
-    let kalshi_input = env::var("KALSHI_TICKER")
-        .context("KALSHI_TICKER not found in .env")?;
-    
+    // A CONFIG_FILE listing many pairs switches the tool into scanner mode.
+    let config_file = env::var("CONFIG_FILE").ok();
+
+    // Manual market configuration (can be event-level slugs/tickers); only
+    // required in single-pair mode.
+    let polymarket_slug = env::var("POLYMARKET_SLUG");
+    let kalshi_input = env::var("KALSHI_TICKER");
+
     let polymarket_ws_url = env::var("POLYMARKET_WS_URL")
         .unwrap_or_else(|_| "wss://ws-subscriptions-clob.polymarket.com/ws/market".to_string());
 
@@ -163,6 +271,61 @@ async fn main() -> Result<()> {
     let mut kalshi_client = KalshiClient::new(kalshi_api_key, kalshi_private_key);
     kalshi_client.set_debug(debug);
 
+    // Signed requests embed a millisecond timestamp, so check local-vs-exchange
+    // clock drift up front and warn before the first signature is rejected.
+    let clock_tolerance_ms = env::var("KALSHI_CLOCK_TOLERANCE_MS")
+        .ok()
+        .and_then(|v| v.parse::<i128>().ok())
+        .unwrap_or(5_000);
+    if let Err(e) = kalshi_client.check_clock_skew(clock_tolerance_ms).await {
+        if debug {
+            println!("{}", format!("⚠️  Clock skew check failed: {}", e).yellow());
+        }
+    }
+
+    // Broadcast mode: wrap one upstream Polymarket connection and fan book
+    // checkpoints out to local consumers. Asset ids come from the config file
+    // when present, otherwise from the single POLYMARKET_SLUG.
+    if let Ok(bind_addr) = env::var("BROADCAST_BIND") {
+        let slugs: Vec<String> = match &config_file {
+            Some(path) => scanner::Config::load(path)?
+                .pairs
+                .into_iter()
+                .map(|p| p.polymarket_slug)
+                .collect(),
+            None => vec![polymarket_slug
+                .clone()
+                .context("POLYMARKET_SLUG not found in .env")?],
+        };
+
+        let mut asset_ids = Vec::with_capacity(slugs.len());
+        for slug in &slugs {
+            let (asset_id, _anchor) =
+                polymarket::get_asset_id_and_anchor(slug, verbose, debug).await?;
+            asset_ids.push(asset_id);
+        }
+
+        let mut upstream = PolymarketClient::new(polymarket_ws_url, asset_ids);
+        upstream.set_debug(debug);
+        let mut broadcaster = broadcaster::PolymarketBroadcaster::new(upstream, bind_addr);
+        broadcaster.set_debug(debug);
+        return broadcaster.run().await;
+    }
+
+    // Scanner mode: monitor every pair in the config file concurrently.
+    if let Some(path) = config_file {
+        let config = scanner::Config::load(&path)?;
+        println!(
+            "{}",
+            format!("🔍 Scanner mode: {} pairs from {}", config.pairs.len(), path).bold().cyan()
+        );
+        return scanner::run(kalshi_client, config, polymarket_ws_url, depth, fee_config, debug).await;
+    }
+
+    // Single-pair mode requires the manual slug/ticker pair.
+    let polymarket_slug = polymarket_slug.context("POLYMARKET_SLUG not found in .env")?;
+    let kalshi_input = kalshi_input.context("KALSHI_TICKER not found in .env")?;
+
     // 1. Resolve Polymarket asset ID and anchor from slug
     if verbose || debug {
         println!("\n{}", "Resolving Polymarket market...".yellow());
@@ -213,7 +376,45 @@ async fn main() -> Result<()> {
         println!("{}", "═".repeat(70).cyan());
     }
     
-    let mut polymarket_client = PolymarketClient::new(polymarket_ws_url, polymarket_asset_id.clone());
+    // Make mode: quote passively on Kalshi around the Polymarket mid instead of
+    // hunting crosses. MAKER=true selects it; the quote band is env-configurable.
+    if env::var("MAKER").map(|v| v.to_lowercase() == "true").unwrap_or(false) {
+        let env_f64 = |key: &str, default: f64| {
+            env::var(key).ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(default)
+        };
+        let spacing = match env::var("MAKER_SPACING").unwrap_or_default().to_lowercase().as_str() {
+            "geometric" => making::Spacing::Geometric,
+            _ => making::Spacing::Linear,
+        };
+        let cfg = making::MakerConfig {
+            venue: "Kalshi".to_string(),
+            market: kalshi_ticker.clone(),
+            band_width: env_f64("MAKER_BAND_WIDTH", 0.05),
+            levels: env::var("MAKER_LEVELS").ok().and_then(|v| v.parse().ok()).unwrap_or(3),
+            spacing,
+            total_capital: env_f64("MAKER_CAPITAL", 100.0),
+            max_inventory: env_f64("MAKER_MAX_INVENTORY", 100.0),
+            min_edge: env_f64("MAKER_MIN_EDGE", 0.0),
+            requote_threshold: env_f64("MAKER_REQUOTE_THRESHOLD", 0.01),
+        };
+        let live = env::var("EXECUTE").map(|v| v.to_lowercase() == "true").unwrap_or(false)
+            || env::args().any(|a| a == "--live");
+        let clob_url = env::var("POLYMARKET_CLOB_URL")
+            .unwrap_or_else(|_| "https://clob.polymarket.com".to_string());
+        return making::run(
+            kalshi_client,
+            cfg,
+            polymarket_ws_url,
+            polymarket_asset_id.clone(),
+            clob_url,
+            live,
+            debug,
+        )
+        .await;
+    }
+
+    let mut polymarket_client =
+        PolymarketClient::new(polymarket_ws_url, vec![polymarket_asset_id.clone()]);
     polymarket_client.set_debug(debug);
 
     // Track latest market data
@@ -222,30 +423,89 @@ async fn main() -> Result<()> {
 
     if verbose || debug {
         println!("\n{}", "Starting arbitrage detection...".yellow());
-        
-        // Connect to Polymarket WebSocket once
-        println!("{}", "Connecting to Polymarket WebSocket...".dimmed());
+        println!("{}", "Subscribing to Polymarket WebSocket...".dimmed());
     }
-    match polymarket_client.connect().await {
-        Ok(_) => {
-            if verbose || debug {
-                println!("{}", "✓ Polymarket connected".green());
-            }
-        },
-        Err(e) => {
-            println!("{}", format!("❌ Failed to connect to Polymarket: {}", e).red());
-            println!("{}", "Hint: Check POLYMARKET_ASSET_ID in .env".yellow());
-            return Ok(());
-        }
+    // Supervised subscription: the spawned task transparently reconnects and
+    // publishes each incrementally-reconstructed book to this receiver, so the
+    // loop never has to drive Polymarket reconnects itself.
+    let mut poly_rx = polymarket_client.subscribe();
+
+    // Subscribe to the Kalshi order book over WebSocket so the loop can select
+    // on both venues' streams instead of timing a REST poll.
+    let mut kalshi_ws = kalshi_client.ws_client(&kalshi_ticker);
+    if let Err(e) = kalshi_ws.connect().await {
+        println!("{}", format!("❌ Failed to connect to Kalshi WebSocket: {}", e).red());
+        return Ok(());
     }
-    
     if verbose || debug {
+        println!("{}", "✓ Kalshi connected".green());
         println!("{}", "Press Ctrl+C to stop\n".dimmed());
     }
 
-    // Track last Kalshi fetch time
-    let mut last_kalshi_fetch = std::time::Instant::now();
-    let kalshi_interval = std::time::Duration::from_secs_f64(poll_interval);
+    // Fills persisted by the execution path drive the periodic PnL panel.
+    let activity_log = env::var("ACTIVITY_LOG").unwrap_or_else(|_| "activity.json".to_string());
+    let mut account = account::Account::load(&activity_log).unwrap_or_default();
+
+    // Optional raw-snapshot recording: SNAPSHOT_DB points the ingestion writer
+    // at a SQLite store that backs the offline analytics/backtest path.
+    #[cfg(feature = "storage")]
+    let snapshot_writer = match env::var("SNAPSHOT_DB") {
+        Ok(path) => match storage::SnapshotWriter::open(&path) {
+            Ok(writer) => {
+                if verbose || debug {
+                    println!("{}", format!("✓ Recording snapshots to {}", path).green());
+                }
+                Some(writer)
+            }
+            Err(e) => {
+                println!("{}", format!("⚠️  Snapshot recording disabled: {}", e).yellow());
+                None
+            }
+        },
+        Err(_) => None,
+    };
+
+    // Optional candle indexing: CANDLE_DB is a Postgres connection string. Each
+    // venue mid feeds a rolling OHLC builder whose finished candles are upserted,
+    // while the raw snapshots are persisted so windows can be re-backfilled.
+    #[cfg(feature = "storage")]
+    let (candle_store, mut candle_builder) = match env::var("CANDLE_DB") {
+        Ok(conn_str) => match tokio_postgres::connect(&conn_str, tokio_postgres::NoTls).await {
+            Ok((client, connection)) => {
+                tokio::spawn(async move {
+                    if let Err(e) = connection.await {
+                        eprintln!("Postgres connection error: {}", e);
+                    }
+                });
+                let store = candles::CandleStore::new(client);
+                match store.init().await {
+                    Ok(()) => {
+                        if verbose || debug {
+                            println!("{}", "✓ Candle indexing enabled".green());
+                        }
+                        (Some(store), candles::CandleBuilder::new())
+                    }
+                    Err(e) => {
+                        println!("{}", format!("⚠️  Candle indexing disabled: {}", e).yellow());
+                        (None, candles::CandleBuilder::new())
+                    }
+                }
+            }
+            Err(e) => {
+                println!("{}", format!("⚠️  Candle indexing disabled: {}", e).yellow());
+                (None, candles::CandleBuilder::new())
+            }
+        },
+        Err(_) => (None, candles::CandleBuilder::new()),
+    };
+
+    // Execution engine: dry-run by default; EXECUTE=true or --live arms it to
+    // actually submit the paired legs of a confirmed opportunity.
+    let live = env::var("EXECUTE").map(|v| v.to_lowercase() == "true").unwrap_or(false)
+        || env::args().any(|a| a == "--live");
+    let clob_url = env::var("POLYMARKET_CLOB_URL")
+        .unwrap_or_else(|_| "https://clob.polymarket.com".to_string());
+    let mut engine = execution::ExecutionEngine::new(&kalshi_client, clob_url, live);
 
     // Throttling for non-verbose mode
     let mut iter_count = 0;
@@ -253,57 +513,78 @@ async fn main() -> Result<()> {
     let display_interval = std::time::Duration::from_secs(10);
 
     loop {
-        // Fetch Kalshi data if interval elapsed
+        // Read the next Kalshi book update from the WebSocket feed, reconnecting
+        // on error just as the Polymarket side does.
         let kalshi_future = async {
-            if last_kalshi_fetch.elapsed() >= kalshi_interval {
-                last_kalshi_fetch = std::time::Instant::now();
-                
-                match kalshi_client.get_market(&kalshi_ticker).await {
-                    Ok(market) => Some(market),
-                    Err(e) => {
-                        println!("{}", format!("\n❌ Kalshi error: {}", e).red());
-                        None
-                    }
-                }
-            } else {
-                None
-            }
-        };
-
-        // Read next Polymarket update (non-blocking with timeout)
-        let polymarket_future = async {
             match tokio::time::timeout(
                 std::time::Duration::from_millis(500),
-                polymarket_client.read_next_book()
+                kalshi_ws.read_next_market(),
             ).await {
                 Ok(Ok(market)) => Some(market),
                 Ok(Err(e)) => {
-                    println!("{}", format!("\n❌ Polymarket error: {}", e).red());
+                    println!("{}", format!("\n❌ Kalshi error: {}", e).red());
                     println!("{}", "Reconnecting...".yellow());
-                    
-                    // Try to reconnect
-                    if let Err(e) = polymarket_client.connect().await {
+                    if let Err(e) = kalshi_ws.connect().await {
                         println!("{}", format!("❌ Reconnect failed: {}", e).red());
                     }
                     None
-                },
+                }
                 Err(_) => None, // Timeout - no new data
             }
         };
 
-        // Run both concurrently
-        let (kalshi_result, polymarket_result) = tokio::join!(kalshi_future, polymarket_future);
-
-        // Process updates
+        // Await the Kalshi read (self-timed), then fold in the latest Polymarket
+        // book the supervised subscription has published.
+        let kalshi_result = kalshi_future.await;
         if let Some(market) = kalshi_result {
             last_kalshi_market = Some(market);
         }
-        if let Some(market) = polymarket_result {
-            last_polymarket_market = Some(market);
+        match &*poly_rx.borrow_and_update() {
+            Ok(market) => last_polymarket_market = Some(market.clone()),
+            Err(e) => {
+                if debug {
+                    println!("{}", format!("Polymarket not ready: {}", e).dimmed());
+                }
+            }
         }
 
         // Display and detect if we have at least one side
         if let (Some(k_market), Some(p_market)) = (&last_kalshi_market, &last_polymarket_market) {
+            // Record the raw book snapshots before acting on them.
+            #[cfg(feature = "storage")]
+            if let Some(writer) = &snapshot_writer {
+                let ts_ms = chrono::Utc::now().timestamp_millis();
+                for row in [
+                    kalshi_snapshot_row(ts_ms, &kalshi_ticker, k_market),
+                    polymarket_snapshot_row(ts_ms, &polymarket_asset_id, p_market),
+                ] {
+                    if let Err(e) = writer.append_snapshot(&row) {
+                        println!("{}", format!("⚠️  Snapshot write failed: {}", e).yellow());
+                    }
+                }
+            }
+
+            // Feed each venue mid into the candle index and persist the raw rows.
+            #[cfg(feature = "storage")]
+            if let Some(store) = &candle_store {
+                let ts_ms = chrono::Utc::now().timestamp_millis();
+                let rows = [
+                    kalshi_snapshot_row(ts_ms, &kalshi_ticker, k_market),
+                    polymarket_snapshot_row(ts_ms, &polymarket_asset_id, p_market),
+                ];
+                let mut finished = Vec::new();
+                for row in &rows {
+                    if let Err(e) = store.append_snapshot(row).await {
+                        println!("{}", format!("⚠️  Candle snapshot write failed: {}", e).yellow());
+                    }
+                    let mid = (row.best_bid + row.best_ask) / 2.0;
+                    finished.extend(candle_builder.ingest(&row.market_id, ts_ms / 1000, mid, row.depth));
+                }
+                if let Err(e) = store.upsert(&finished).await {
+                    println!("{}", format!("⚠️  Candle upsert failed: {}", e).yellow());
+                }
+            }
+
             let should_display = if verbose {
                 true 
             } else {
@@ -312,18 +593,78 @@ async fn main() -> Result<()> {
 
             if should_display {
                 display_markets(&anchor, k_market, p_market);
+                // Mark open positions to each venue's current mid for the panel.
+                let mut marks = std::collections::HashMap::new();
+                marks.insert(
+                    ("Kalshi".to_string(), kalshi_ticker.clone()),
+                    (k_market.yes_bid as f64 + k_market.yes_ask as f64) / 200.0,
+                );
+                marks.insert(
+                    ("Polymarket".to_string(), polymarket_asset_id.clone()),
+                    (p_market.best_bid + p_market.best_ask) / 2.0,
+                );
+                // Reconcile against Kalshi's reported balance and positions.
+                let venue_state = fetch_kalshi_state(&kalshi_client).await;
+                account::render_pnl_panel(&account, &marks, venue_state.as_ref());
                 iter_count = 0;
                 last_display_time = std::time::Instant::now();
             } else {
                 iter_count += 1;
             }
 
-            // Detect arbitrage - ALWAYS display if found
-            if let Some(opportunity) = detect_arbitrage(k_market, p_market) {
+            // Detect arbitrage across the venue set via the generic engine, then
+            // ALWAYS display if a net-positive cross is found.
+            let exchanges: Vec<Box<dyn Exchange>> = vec![
+                Box::new(KalshiExchange { market: k_market.clone(), fees: fee_config.clone() }),
+                Box::new(PolymarketExchange {
+                    asset_id: polymarket_asset_id.clone(),
+                    latest: p_market.clone(),
+                    fees: fee_config.clone(),
+                }),
+            ];
+            let market_ids = vec![kalshi_ticker.clone(), polymarket_asset_id.clone()];
+            if let Some(opportunity) =
+                exchange::detect_opportunities(&exchanges, &market_ids, depth).await.into_iter().next()
+            {
                 if !should_display {
                     display_markets(&anchor, k_market, p_market);
                 }
                 display_arbitrage(&opportunity);
+
+                // Act on the cross: dry-run logs intended legs, live submits them.
+                let submitted_before = engine.submitted.len();
+                if let Err(e) = engine
+                    .execute(&opportunity, &kalshi_ticker, &polymarket_asset_id)
+                    .await
+                {
+                    println!("{}", format!("❌ Execution failed: {}", e).red());
+                }
+                // Record any newly submitted legs as fills so the PnL panel and
+                // activity log reflect the live execution.
+                if engine.submitted.len() > submitted_before {
+                    let ts_ms = chrono::Utc::now().timestamp_millis();
+                    for order in &engine.submitted[submitted_before..] {
+                        account.record(account::Fill {
+                            ts_ms,
+                            venue: order.platform.clone(),
+                            market_id: order.market.clone(),
+                            is_buy: order.is_buy,
+                            price: order.price,
+                            size: order.size,
+                        });
+                    }
+                    if let Err(e) = account.save(&activity_log) {
+                        println!("{}", format!("⚠️  Failed to persist activity log: {}", e).yellow());
+                    }
+                }
+
+                #[cfg(feature = "storage")]
+                if let Some(writer) = &snapshot_writer {
+                    let ts_ms = chrono::Utc::now().timestamp_millis();
+                    if let Err(e) = writer.append_opportunity(ts_ms, &polymarket_asset_id, &opportunity) {
+                        println!("{}", format!("⚠️  Opportunity write failed: {}", e).yellow());
+                    }
+                }
             }
         }
 
@@ -0,0 +1,194 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::execution::ExecutionEngine;
+use crate::kalshi::KalshiClient;
+use crate::polymarket::PolymarketClient;
+
+/// Spacing of quote levels across the band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spacing {
+    Linear,
+    Geometric,
+}
+
+/// Parameters for the passive `make` mode.
+#[derive(Debug, Clone)]
+pub struct MakerConfig {
+    /// The venue we post quotes on.
+    pub venue: String,
+    /// The market id / ticker on `venue`.
+    pub market: String,
+    /// Half-width of the quoting band, in dollars either side of the fair price.
+    pub band_width: f64,
+    /// Number of levels per side (`K`).
+    pub levels: usize,
+    pub spacing: Spacing,
+    /// Total capital (dollars) spread across all resting quotes.
+    pub total_capital: f64,
+    /// Inventory (contracts) at which the flattening skew reaches the full band
+    /// width; below it the skew grows in proportion to inventory.
+    pub max_inventory: f64,
+    /// Minimum required edge over fees (dollars) before quoting at all.
+    pub min_edge: f64,
+    /// Re-quote when the reference mid moves more than this (dollars).
+    pub requote_threshold: f64,
+}
+
+/// A single resting quote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Compute a symmetric quote ladder around `fair`, skewed by current
+/// `inventory` so quotes pull the book back toward neutral. Returns up to `K`
+/// bids and `K` asks (a bid at or below 0 or an ask at or above 1 is dropped),
+/// or an empty ladder when the band is not wider than the required edge over
+/// fees.
+pub fn build_ladder(fair: f64, inventory: f64, cfg: &MakerConfig) -> Vec<Quote> {
+    if cfg.levels == 0 || cfg.band_width <= cfg.min_edge {
+        return Vec::new();
+    }
+
+    // Skew: as inventory grows long, lower the whole ladder so asks fill first;
+    // as it grows short, raise it so bids fill first.
+    let skew = if cfg.max_inventory > 0.0 {
+        (inventory / cfg.max_inventory).clamp(-1.0, 1.0) * cfg.band_width
+    } else {
+        0.0
+    };
+    let center = fair - skew;
+
+    let per_level_size = (cfg.total_capital / (2.0 * cfg.levels as f64)).max(0.0);
+    let mut quotes = Vec::with_capacity(cfg.levels * 2);
+
+    for i in 0..cfg.levels {
+        let offset = level_offset(i, cfg);
+        let bid = center - offset;
+        let ask = center + offset;
+        if bid > 0.0 {
+            quotes.push(Quote { is_buy: true, price: round_tick(bid), size: per_level_size });
+        }
+        if ask < 1.0 {
+            quotes.push(Quote { is_buy: false, price: round_tick(ask), size: per_level_size });
+        }
+    }
+    quotes
+}
+
+/// Offset of level `i` from the center, per the configured spacing.
+fn level_offset(i: usize, cfg: &MakerConfig) -> f64 {
+    let step = cfg.band_width / cfg.levels as f64;
+    match cfg.spacing {
+        Spacing::Linear => step * (i as f64 + 1.0),
+        // Geometric levels cluster near the center and widen outward.
+        Spacing::Geometric => cfg.band_width * (0.5f64).powi((cfg.levels - i - 1) as i32),
+    }
+}
+
+fn round_tick(price: f64) -> f64 {
+    (price * 100.0).round() / 100.0
+}
+
+/// Manages a resting quote ladder on one venue, referenced to the other
+/// venue's mid, cancelling and replacing quotes as the reference moves.
+pub struct MarketMaker {
+    cfg: MakerConfig,
+    reference_mid: Option<f64>,
+    active: Vec<(String, Quote)>,
+}
+
+impl MarketMaker {
+    pub fn new(cfg: MakerConfig) -> Self {
+        Self { cfg, reference_mid: None, active: Vec::new() }
+    }
+
+    /// React to a fresh reference mid. When it has moved past the re-quote
+    /// threshold, cancel the resting ladder and place a freshly-skewed one.
+    pub async fn on_reference_mid(
+        &mut self,
+        reference_mid: f64,
+        inventory: f64,
+        engine: &mut ExecutionEngine<'_>,
+    ) -> Result<()> {
+        if let Some(prev) = self.reference_mid {
+            if (reference_mid - prev).abs() < self.cfg.requote_threshold {
+                return Ok(());
+            }
+        }
+        self.reference_mid = Some(reference_mid);
+
+        // Cancel the resting ladder before replacing it.
+        for (order_id, _quote) in std::mem::take(&mut self.active) {
+            let _ = engine.cancel_kalshi(&order_id).await;
+        }
+
+        for quote in build_ladder(reference_mid, inventory, &self.cfg) {
+            let id = engine
+                .place_quote(&self.cfg.venue, &self.cfg.market, quote.is_buy, quote.price, quote.size)
+                .await?;
+            self.active.push((id, quote));
+        }
+        Ok(())
+    }
+}
+
+/// Run the passive `make` mode: quote a skewed ladder on `cfg.venue` referenced
+/// to the other venue's streamed mid, re-quoting when the reference moves and
+/// skewing by the resting position on the quoted market.
+pub async fn run(
+    kalshi_client: KalshiClient,
+    cfg: MakerConfig,
+    poly_ws_url: String,
+    poly_asset_id: String,
+    clob_url: String,
+    live: bool,
+    debug: bool,
+) -> Result<()> {
+    let mut reference = PolymarketClient::new(poly_ws_url, vec![poly_asset_id]);
+    reference.set_debug(debug);
+    reference.connect().await?;
+
+    let mut engine = ExecutionEngine::new(&kalshi_client, clob_url, live);
+    let mut maker = MarketMaker::new(cfg.clone());
+
+    println!(
+        "{}",
+        format!(
+            "🪙 Market-making {} on {} ({})",
+            cfg.market,
+            cfg.venue,
+            if live { "LIVE" } else { "dry-run" }
+        )
+        .bold()
+        .cyan()
+    );
+
+    loop {
+        match reference.read_next_update().await {
+            Ok(market) => {
+                let mid = (market.best_bid + market.best_ask) / 2.0;
+                if mid <= 0.0 {
+                    continue;
+                }
+                // Skew by the current resting position on the quoted market.
+                let inventory = kalshi_client
+                    .get_positions()
+                    .await
+                    .ok()
+                    .and_then(|ps| ps.into_iter().find(|p| p.ticker == cfg.market))
+                    .map(|p| p.position as f64)
+                    .unwrap_or(0.0);
+                maker.on_reference_mid(mid, inventory, &mut engine).await?;
+            }
+            Err(e) => {
+                println!("{}", format!("❌ Reference feed error: {}", e).red());
+                println!("{}", "Reconnecting...".yellow());
+                let _ = reference.connect().await;
+            }
+        }
+    }
+}
@@ -3,8 +3,33 @@ use colored::Colorize;
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
 use serde_json::json;
-use tokio_tungstenite::{connect_async, tungstenite::Message, WebSocketStream, MaybeTlsStream};
+use ordered_float::OrderedFloat;
+use std::collections::BTreeMap;
+use std::time::Duration;
 use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio_tungstenite::{connect_async, tungstenite::Message, WebSocketStream, MaybeTlsStream};
+
+/// Typed Polymarket streaming errors.
+#[derive(Debug, Clone)]
+pub enum PolymarketError {
+    /// The CRC32 computed over the maintained book disagreed with the checksum
+    /// carried on the update, implying a dropped or mis-ordered delta. The
+    /// reconnecting client should force a fresh snapshot.
+    ChecksumMismatch { expected: u32, computed: u32 },
+}
+
+impl std::fmt::Display for PolymarketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolymarketError::ChecksumMismatch { expected, computed } => {
+                write!(f, "order book checksum mismatch: expected {}, computed {}", expected, computed)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PolymarketError {}
 
 /// Polymarket orderbook level
 #[derive(Debug, Deserialize, Clone)]
@@ -14,6 +39,63 @@ pub struct OrderbookLevel {
     pub size: String,
 }
 
+/// A single changed level inside a `price_change` event.
+#[derive(Debug, Deserialize, Clone)]
+#[allow(dead_code)]
+pub struct PriceChange {
+    pub price: String,
+    pub size: String,
+    pub side: String,
+}
+
+/// A typed Polymarket market-channel event, demultiplexed by `asset_id`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "event_type")]
+#[allow(dead_code)]
+pub enum PolymarketEvent {
+    #[serde(rename = "book")]
+    Book {
+        asset_id: String,
+        #[serde(default)]
+        bids: Vec<OrderbookLevel>,
+        #[serde(default)]
+        asks: Vec<OrderbookLevel>,
+        #[serde(default)]
+        hash: Option<String>,
+    },
+    #[serde(rename = "price_change")]
+    PriceChange {
+        asset_id: String,
+        #[serde(default)]
+        changes: Vec<PriceChange>,
+    },
+    #[serde(rename = "tick_size_change")]
+    TickSizeChange {
+        asset_id: String,
+        old_tick_size: String,
+        new_tick_size: String,
+    },
+    #[serde(rename = "last_trade_price")]
+    LastTradePrice {
+        asset_id: String,
+        price: String,
+        size: String,
+        side: String,
+    },
+}
+
+impl PolymarketEvent {
+    /// The asset id this event pertains to.
+    pub fn asset_id(&self) -> &str {
+        match self {
+            PolymarketEvent::Book { asset_id, .. }
+            | PolymarketEvent::PriceChange { asset_id, .. }
+            | PolymarketEvent::TickSizeChange { asset_id, .. }
+            | PolymarketEvent::LastTradePrice { asset_id, .. } => asset_id,
+        }
+    }
+}
+
 /// Polymarket market data
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -25,24 +107,48 @@ pub struct PolymarketMarket {
     pub asks: Vec<OrderbookLevel>,
 }
 
+/// A maintained order-book level, keyed in the book by its parsed float price.
+/// The venue's original `price`/`size` strings are retained verbatim so the
+/// checksum can be recomputed over the exact text the exchange hashed rather
+/// than a reformatted float.
+#[derive(Debug, Clone)]
+struct BookLevel {
+    price_repr: String,
+    size_repr: String,
+}
+
 /// Polymarket WebSocket client
 pub struct PolymarketClient {
     ws_url: String,
     ws_stream: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-    asset_id: String,
+    asset_ids: Vec<String>,
     debug: bool,
+    /// Local order book maintained from deltas, keyed by price. Populated only
+    /// in the single-asset `read_next_update` path.
+    bids: BTreeMap<OrderedFloat<f64>, BookLevel>,
+    asks: BTreeMap<OrderedFloat<f64>, BookLevel>,
 }
 
 impl PolymarketClient {
-    pub fn new(ws_url: String, asset_id: String) -> Self {
-        Self { 
+    /// Create a client subscribing to one or more asset ids on a single
+    /// connection. Updates are demultiplexed by the `asset_id` field.
+    pub fn new(ws_url: String, asset_ids: Vec<String>) -> Self {
+        Self {
             ws_url,
             ws_stream: None,
-            asset_id,
+            asset_ids,
             debug: false,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
         }
     }
 
+    /// The first subscribed asset id, used as the `token_id` on books
+    /// reconstructed by the single-asset path.
+    fn primary_asset(&self) -> String {
+        self.asset_ids.first().cloned().unwrap_or_default()
+    }
+
     pub fn set_debug(&mut self, debug: bool) {
         self.debug = debug;
     }
@@ -59,7 +165,7 @@ impl PolymarketClient {
         // Subscribe to market - matches Python format
         let subscribe_msg = json!({
             "auth": {},
-            "assets_ids": [&self.asset_id],
+            "assets_ids": &self.asset_ids,
             "type": "MARKET"
         });
 
@@ -74,88 +180,292 @@ impl PolymarketClient {
         Ok(())
     }
 
-    /// Read next orderbook update from WebSocket
-    pub async fn read_next_book(&mut self) -> Result<PolymarketMarket> {
-        let ws_stream = self.ws_stream.as_mut()
-            .ok_or_else(|| anyhow::anyhow!("Not connected. Call connect() first"))?;
-
-        // Read messages until we get a book update
-        while let Some(msg) = ws_stream.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if self.debug {
-                        println!("\n{} {}", "[DEBUG] Polymarket Raw Message:".yellow().bold(), text);
-                    }
-                    // Parse as array or single object
-                    if let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) {
-                        // Handle array of messages
-                        let messages = if data.is_array() {
-                            data.as_array().unwrap().clone()
-                        } else {
-                            vec![data]
-                        };
-
-                        for msg_data in messages {
-                            let event_type = msg_data["event_type"].as_str()
-                                .or_else(|| msg_data["type"].as_str());
-
-                            if event_type == Some("book") {
-                                return self.parse_orderbook(&msg_data);
+    /// Spawn a supervised streaming task that transparently reconnects on any
+    /// disconnect and publishes each incrementally-reconstructed book to a
+    /// [`watch`] channel.
+    /// Consumers just read the latest book from the receiver and never observe
+    /// a transient drop; reconnection uses exponential backoff (initial 1s,
+    /// capped at 60s, reset on a successful message) and retries until every
+    /// receiver has been dropped.
+    pub fn subscribe(&self) -> watch::Receiver<Result<PolymarketMarket, String>> {
+        let ws_url = self.ws_url.clone();
+        let asset_ids = self.asset_ids.clone();
+        let debug = self.debug;
+        let (tx, rx) = watch::channel(Err("connecting".to_string()));
+
+        tokio::spawn(async move {
+            const INITIAL: Duration = Duration::from_secs(1);
+            const MAX: Duration = Duration::from_secs(60);
+            let mut backoff = INITIAL;
+
+            loop {
+                let mut client = PolymarketClient::new(ws_url.clone(), asset_ids.clone());
+                client.set_debug(debug);
+
+                match client.connect().await {
+                    Ok(()) => loop {
+                        match client.read_next_update().await {
+                            Ok(market) => {
+                                backoff = INITIAL; // Reset on a successful message.
+                                if tx.send(Ok(market)).is_err() {
+                                    return; // All receivers dropped.
+                                }
+                            }
+                            Err(e) => {
+                                if debug {
+                                    println!("{}", format!("Polymarket stream dropped: {}", e).yellow());
+                                }
+                                break;
                             }
                         }
+                    },
+                    Err(e) => {
+                        if debug {
+                            println!("{}", format!("Polymarket connect failed: {}", e).yellow());
+                        }
                     }
                 }
-                Ok(Message::Close(_)) => {
-                    return Err(anyhow::anyhow!("WebSocket closed"));
+
+                // The consumer must survive the drop, so exit only if nobody is
+                // listening; otherwise back off and reconnect.
+                if tx.is_closed() {
+                    return;
                 }
-                Err(e) => {
-                    return Err(anyhow::anyhow!("WebSocket error: {}", e));
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX);
+            }
+        });
+
+        rx
+    }
+
+    /// Read the next event and return the reconstructed book after applying it.
+    /// A `book` snapshot re-seeds the local maps; a `price_change` delta mutates
+    /// them level-by-level, so callers get tick-accurate depth without waiting
+    /// for the next snapshot.
+    pub async fn read_next_update(&mut self) -> Result<PolymarketMarket> {
+        loop {
+            let ws_stream = self
+                .ws_stream
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("Not connected. Call connect() first"))?;
+
+            let Some(msg) = ws_stream.next().await else {
+                return Err(anyhow::anyhow!("No orderbook data received"));
+            };
+
+            let text = match msg {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return Err(anyhow::anyhow!("WebSocket closed")),
+                Ok(_) => continue,
+                Err(e) => return Err(anyhow::anyhow!("WebSocket error: {}", e)),
+            };
+
+            if self.debug {
+                println!("\n{} {}", "[DEBUG] Polymarket Raw Message:".yellow().bold(), text);
+            }
+
+            let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) else {
+                continue;
+            };
+            let messages = if data.is_array() {
+                data.as_array().cloned().unwrap_or_default()
+            } else {
+                vec![data]
+            };
+
+            for msg_data in messages {
+                let event_type = msg_data["event_type"].as_str().or_else(|| msg_data["type"].as_str());
+                match event_type {
+                    Some("book") => {
+                        self.seed_book(&msg_data);
+                        self.verify_checksum(&msg_data)?;
+                        return Ok(self.rebuild_market());
+                    }
+                    Some("price_change") => {
+                        self.apply_price_change(&msg_data);
+                        self.verify_checksum(&msg_data)?;
+                        return Ok(self.rebuild_market());
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
         }
+    }
+
+    /// Read the next typed event from the connection, returning it alongside
+    /// the asset id it pertains to so one connection can drive many markets.
+    pub async fn read_next_event(&mut self) -> Result<(String, PolymarketEvent)> {
+        loop {
+            let ws_stream = self
+                .ws_stream
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("Not connected. Call connect() first"))?;
+
+            let Some(msg) = ws_stream.next().await else {
+                return Err(anyhow::anyhow!("No event data received"));
+            };
+
+            let text = match msg {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return Err(anyhow::anyhow!("WebSocket closed")),
+                Ok(_) => continue,
+                Err(e) => return Err(anyhow::anyhow!("WebSocket error: {}", e)),
+            };
+
+            if self.debug {
+                println!("\n{} {}", "[DEBUG] Polymarket Raw Message:".yellow().bold(), text);
+            }
+
+            let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) else {
+                continue;
+            };
+            let messages = if data.is_array() {
+                data.as_array().cloned().unwrap_or_default()
+            } else {
+                vec![data]
+            };
 
-        Err(anyhow::anyhow!("No orderbook data received"))
+            for msg_data in messages {
+                // Unknown/irrelevant event types simply fail to deserialize.
+                if let Ok(event) = serde_json::from_value::<PolymarketEvent>(msg_data) {
+                    return Ok((event.asset_id().to_string(), event));
+                }
+            }
+        }
     }
 
-    fn parse_orderbook(&self, data: &serde_json::Value) -> Result<PolymarketMarket> {
-        let mut bids: Vec<OrderbookLevel> = serde_json::from_value(
-            data["bids"].clone()
-        ).unwrap_or_default();
-        
-        let mut asks: Vec<OrderbookLevel> = serde_json::from_value(
-            data["asks"].clone()
-        ).unwrap_or_default();
-
-        // Sort bids descending (best bid at the top)
-        bids.sort_by(|a, b| {
-            let a_p = a.price.parse::<f64>().unwrap_or(0.0);
-            let b_p = b.price.parse::<f64>().unwrap_or(0.0);
-            b_p.partial_cmp(&a_p).unwrap_or(std::cmp::Ordering::Equal)
-        });
+    /// Consume the client into a `Stream` of `(asset_id, event)` items,
+    /// ending when the connection drops.
+    pub fn into_events(self) -> impl futures_util::Stream<Item = (String, PolymarketEvent)> {
+        futures_util::stream::unfold(self, |mut client| async move {
+            match client.read_next_event().await {
+                Ok(item) => Some((item, client)),
+                Err(_) => None,
+            }
+        })
+    }
 
-        // Sort asks ascending (best ask at the top)
-        asks.sort_by(|a, b| {
-            let a_p = a.price.parse::<f64>().unwrap_or(0.0);
-            let b_p = b.price.parse::<f64>().unwrap_or(0.0);
-            a_p.partial_cmp(&b_p).unwrap_or(std::cmp::Ordering::Equal)
-        });
+    /// Re-seed the local maps wholesale from a full `book` snapshot.
+    fn seed_book(&mut self, data: &serde_json::Value) {
+        self.bids.clear();
+        self.asks.clear();
+        for level in data["bids"].as_array().into_iter().flatten() {
+            if let Some((price, entry)) = book_level(level) {
+                self.bids.insert(OrderedFloat(price), entry);
+            }
+        }
+        for level in data["asks"].as_array().into_iter().flatten() {
+            if let Some((price, entry)) = book_level(level) {
+                self.asks.insert(OrderedFloat(price), entry);
+            }
+        }
+    }
 
-        // Best bid is the first item after sorting
-        let best_bid = bids.first()
-            .and_then(|b| b.price.parse::<f64>().ok())
-            .unwrap_or(0.0);
+    /// Apply each changed level from a `price_change` delta, setting the size at
+    /// that price and removing the key entirely when the size is zero.
+    fn apply_price_change(&mut self, data: &serde_json::Value) {
+        let changes = data["changes"].as_array().cloned().unwrap_or_else(|| vec![data.clone()]);
+        for change in &changes {
+            let Some(price_repr) = change["price"].as_str().map(|p| p.to_string()) else {
+                continue;
+            };
+            let Ok(price) = price_repr.parse::<f64>() else {
+                continue;
+            };
+            let size_repr = change["size"].as_str().unwrap_or("0").to_string();
+            let size = size_repr.parse::<f64>().unwrap_or(0.0);
+            let side = change["side"].as_str().unwrap_or("");
+            let book = if side.eq_ignore_ascii_case("sell") || side.eq_ignore_ascii_case("ask") {
+                &mut self.asks
+            } else {
+                &mut self.bids
+            };
+            if size == 0.0 {
+                book.remove(&OrderedFloat(price));
+            } else {
+                book.insert(OrderedFloat(price), BookLevel { price_repr, size_repr });
+            }
+        }
+    }
 
-        let best_ask = asks.first()
-            .and_then(|a| a.price.parse::<f64>().ok())
-            .unwrap_or(0.0);
+    /// Verify the optional order-book hash carried on an update against the
+    /// maintained book. Polymarket publishes it in the `hash` field.
+    ///
+    /// NOTE: Polymarket's `hash` is an opaque digest, **not** a CRC32 over the
+    /// `price:size` levels, so the local [`checksum`](Self::checksum) cannot
+    /// reproduce it. We therefore only enforce the check when the published hash
+    /// is a CRC32-style integer (as CRC-checksummed venues emit); an opaque
+    /// string digest is accepted as-is so a scheme mismatch can't force an
+    /// endless resnapshot on every message. On a CRC mismatch we return
+    /// [`PolymarketError::ChecksumMismatch`] so the caller can resnapshot.
+    fn verify_checksum(&self, data: &serde_json::Value) -> Result<()> {
+        let Some(hash) = data["hash"].as_str().filter(|s| !s.is_empty()) else {
+            return Ok(()); // Venue did not publish a hash on this message.
+        };
+        let Ok(expected) = hash.parse::<u32>() else {
+            return Ok(()); // Opaque digest; not a CRC32 we can recompute.
+        };
+        let computed = self.checksum();
+        if computed != expected {
+            return Err(PolymarketError::ChecksumMismatch { expected, computed }.into());
+        }
+        Ok(())
+    }
 
-        Ok(PolymarketMarket {
-            token_id: self.asset_id.clone(),
+    /// CRC32 over the canonical top-25 string: levels alternate bid/ask as
+    /// `price:size`, joined with `:`, using each number's string form to avoid
+    /// float-formatting drift.
+    fn checksum(&self) -> u32 {
+        let bids: Vec<(&OrderedFloat<f64>, &BookLevel)> = self.bids.iter().rev().collect();
+        let asks: Vec<(&OrderedFloat<f64>, &BookLevel)> = self.asks.iter().collect();
+        let mut parts: Vec<String> = Vec::with_capacity(100);
+        for i in 0..25 {
+            if let Some((_, l)) = bids.get(i) {
+                parts.push(format!("{}:{}", l.price_repr, l.size_repr));
+            }
+            if let Some((_, l)) = asks.get(i) {
+                parts.push(format!("{}:{}", l.price_repr, l.size_repr));
+            }
+        }
+        crc32fast::hash(parts.join(":").as_bytes())
+    }
+
+    /// Project the maintained maps into a [`PolymarketMarket`]; best bid/ask are
+    /// the highest bid and lowest ask key respectively.
+    fn rebuild_market(&self) -> PolymarketMarket {
+        let bids: Vec<OrderbookLevel> = self
+            .bids
+            .iter()
+            .rev()
+            .map(|(_, l)| OrderbookLevel { price: l.price_repr.clone(), size: l.size_repr.clone() })
+            .collect();
+        let asks: Vec<OrderbookLevel> = self
+            .asks
+            .iter()
+            .map(|(_, l)| OrderbookLevel { price: l.price_repr.clone(), size: l.size_repr.clone() })
+            .collect();
+        let best_bid = self.bids.keys().next_back().map(|p| p.0).unwrap_or(0.0);
+        let best_ask = self.asks.keys().next().map(|p| p.0).unwrap_or(0.0);
+
+        PolymarketMarket {
+            token_id: self.primary_asset(),
             best_bid,
             best_ask,
             bids,
             asks,
-        })
+        }
     }
+
+}
+
+/// Parse a `{ "price": "..", "size": ".." }` level into its keyed float price
+/// and a [`BookLevel`] that retains the venue's original string forms.
+fn book_level(level: &serde_json::Value) -> Option<(f64, BookLevel)> {
+    let price_repr = level["price"].as_str()?.to_string();
+    let size_repr = level["size"].as_str()?.to_string();
+    let price = price_repr.parse::<f64>().ok()?;
+    // Validate the size parses, but keep only the original string form.
+    size_repr.parse::<f64>().ok()?;
+    Some((price, BookLevel { price_repr, size_repr }))
 }
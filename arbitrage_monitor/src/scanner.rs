@@ -0,0 +1,236 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::arbitrage::{detect_arbitrage, ArbitrageOpportunity};
+use crate::broadcaster::AssetBook;
+use crate::fees::FeeConfig;
+use crate::kalshi::{KalshiClient, KalshiMarket};
+use crate::polymarket::{self, PolymarketClient, PolymarketMarket};
+
+/// One Kalshi/Polymarket market pair to monitor.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PairConfig {
+    /// Display name; defaults to the Polymarket slug when omitted.
+    pub name: Option<String>,
+    pub polymarket_slug: String,
+    pub kalshi_ticker: String,
+}
+
+/// A slate of pairs loaded from a TOML or JSON config file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub pairs: Vec<PairConfig>,
+}
+
+impl Config {
+    /// Load a slate from `path`, picking the parser by file extension.
+    pub fn load(path: &str) -> Result<Config> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path))?;
+        if path.ends_with(".json") {
+            serde_json::from_str(&text).context("Failed to parse JSON config")
+        } else {
+            toml::from_str(&text).context("Failed to parse TOML config")
+        }
+    }
+}
+
+/// An opportunity update emitted by a per-pair task into the ranked board.
+struct Update {
+    pair: String,
+    opportunity: Option<ArbitrageOpportunity>,
+}
+
+/// A resolved pair: canonical ids plus the display name.
+struct ResolvedPair {
+    name: String,
+    asset_id: String,
+    ticker: String,
+}
+
+/// The latest Polymarket checkpoint per asset id, fed by the single upstream
+/// connection and read by every per-pair detector.
+type PolyBooks = Arc<Mutex<HashMap<String, PolymarketMarket>>>;
+
+/// Run the multi-pair scanner: resolve every pair, fan one shared Polymarket
+/// connection out to per-pair detectors that each keep their own Kalshi feed,
+/// and render a single ranked board of live opportunities.
+pub async fn run(
+    kalshi_client: KalshiClient,
+    config: Config,
+    poly_ws_url: String,
+    depth: usize,
+    fee_config: FeeConfig,
+    debug: bool,
+) -> Result<()> {
+    let kalshi = Arc::new(kalshi_client);
+
+    // Resolve every pair up front so the shared connection can subscribe to all
+    // asset ids at once. A pair that fails to resolve is dropped with a warning.
+    let mut resolved: Vec<ResolvedPair> = Vec::new();
+    for pair in config.pairs {
+        let name = pair.name.clone().unwrap_or_else(|| pair.polymarket_slug.clone());
+        let (asset_id, anchor) =
+            match polymarket::get_asset_id_and_anchor(&pair.polymarket_slug, false, debug).await {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    println!("{}", format!("❌ Pair '{}' skipped: {}", name, e).red());
+                    continue;
+                }
+            };
+        match kalshi.resolve_market_ticker(&pair.kalshi_ticker, Some(&anchor)).await {
+            Ok(ticker) => resolved.push(ResolvedPair { name, asset_id, ticker }),
+            Err(e) => println!("{}", format!("❌ Pair '{}' skipped: {}", name, e).red()),
+        }
+    }
+
+    let books: PolyBooks = Arc::new(Mutex::new(HashMap::new()));
+    let (tx, rx) = mpsc::unbounded_channel::<Update>();
+
+    // One upstream Polymarket connection subscribes to every asset id and
+    // demultiplexes events into the shared per-asset checkpoint map.
+    let asset_ids: Vec<String> = resolved.iter().map(|r| r.asset_id.clone()).collect();
+    {
+        let books = Arc::clone(&books);
+        tokio::spawn(pump_polymarket(poly_ws_url, asset_ids, books, debug));
+    }
+
+    for r in resolved {
+        let kalshi = Arc::clone(&kalshi);
+        let tx = tx.clone();
+        let books = Arc::clone(&books);
+        let fee_config = fee_config.clone();
+        // Each pair runs independently so one failing market can't stall others.
+        tokio::spawn(async move {
+            let name = r.name.clone();
+            if let Err(e) = monitor_pair(kalshi, r, books, depth, fee_config, tx).await {
+                println!("{}", format!("❌ Pair '{}' stopped: {}", name, e).red());
+            }
+        });
+    }
+    drop(tx);
+
+    aggregate(rx).await;
+    Ok(())
+}
+
+/// Drive the one shared Polymarket connection, reconnecting with exponential
+/// backoff, and fold every demultiplexed event into the shared checkpoint map.
+async fn pump_polymarket(poly_ws_url: String, asset_ids: Vec<String>, books: PolyBooks, debug: bool) {
+    const INITIAL: Duration = Duration::from_secs(1);
+    const MAX: Duration = Duration::from_secs(60);
+
+    let mut client = PolymarketClient::new(poly_ws_url, asset_ids);
+    client.set_debug(debug);
+    let mut asset_books: HashMap<String, AssetBook> = HashMap::new();
+    let mut backoff = INITIAL;
+
+    loop {
+        if client.connect().await.is_ok() {
+            backoff = INITIAL;
+            while let Ok((asset_id, event)) = client.read_next_event().await {
+                let book = asset_books.entry(asset_id.clone()).or_default();
+                book.apply(&event);
+                let checkpoint = book.checkpoint(&asset_id);
+                books.lock().await.insert(asset_id, checkpoint);
+            }
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX);
+    }
+}
+
+/// Monitor one resolved pair: keep a Kalshi feed, read the shared Polymarket
+/// checkpoint, and emit ranked-board updates. Reconnects its Kalshi socket on
+/// error without disturbing sibling tasks.
+async fn monitor_pair(
+    kalshi: Arc<KalshiClient>,
+    pair: ResolvedPair,
+    books: PolyBooks,
+    depth: usize,
+    fee_config: FeeConfig,
+    tx: mpsc::UnboundedSender<Update>,
+) -> Result<()> {
+    let mut kalshi_ws = kalshi.ws_client(&pair.ticker);
+    kalshi_ws.connect().await?;
+
+    let mut last_kalshi: Option<KalshiMarket> = None;
+    let read_timeout = Duration::from_millis(500);
+
+    loop {
+        match tokio::time::timeout(read_timeout, kalshi_ws.read_next_market()).await {
+            Ok(Ok(market)) => last_kalshi = Some(market),
+            Ok(Err(_)) => {
+                // Isolated reconnect for just this pair.
+                let _ = kalshi_ws.connect().await;
+            }
+            Err(_) => {}
+        }
+
+        let last_poly = books.lock().await.get(&pair.asset_id).cloned();
+        if let (Some(k), Some(p)) = (&last_kalshi, &last_poly) {
+            let opportunity = detect_arbitrage(k, p, depth, &fee_config);
+            if tx.send(Update { pair: pair.name.clone(), opportunity }).is_err() {
+                break; // Aggregator gone.
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collect per-pair updates and periodically print a board ranked by net profit.
+async fn aggregate(mut rx: mpsc::UnboundedReceiver<Update>) {
+    let mut board: HashMap<String, ArbitrageOpportunity> = HashMap::new();
+    let mut last_render = std::time::Instant::now();
+    let render_interval = std::time::Duration::from_secs(2);
+
+    while let Some(update) = rx.recv().await {
+        match update.opportunity {
+            Some(opp) => {
+                board.insert(update.pair, opp);
+            }
+            None => {
+                board.remove(&update.pair);
+            }
+        }
+
+        if last_render.elapsed() >= render_interval {
+            render_board(&board);
+            last_render = std::time::Instant::now();
+        }
+    }
+}
+
+fn render_board(board: &HashMap<String, ArbitrageOpportunity>) {
+    let now = chrono::Local::now().format("%H:%M:%S");
+    println!("\n{}", "═".repeat(90).cyan());
+    println!("{}", format!("[{}] Arbitrage scanner ({} live)", now, board.len()).bold());
+    println!("{}", "─".repeat(90).dimmed());
+
+    let mut ranked: Vec<(&String, &ArbitrageOpportunity)> = board.iter().collect();
+    ranked.sort_by(|a, b| {
+        b.1.net_profit
+            .partial_cmp(&a.1.net_profit)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    if ranked.is_empty() {
+        println!("{}", "  no net-positive opportunities".dimmed());
+    }
+    for (pair, opp) in ranked {
+        println!(
+            "  {:<28} {} -> {}  net {:.2}¢  size {:.0}",
+            pair.cyan(),
+            opp.buy_platform,
+            opp.sell_platform,
+            opp.net_profit,
+            opp.max_size
+        );
+    }
+    println!("{}", "═".repeat(90).cyan());
+}
@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+use crate::arbitrage::ArbitrageOpportunity;
+
+/// One persisted book snapshot. Prices are dollars; `depth` is the summed
+/// resting size across the recorded levels.
+#[derive(Debug, Clone)]
+pub struct SnapshotRow {
+    pub ts_ms: i64,
+    pub venue: String,
+    pub market_id: String,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub depth: f64,
+    pub spread: f64,
+}
+
+/// A derived spread candle over a time bucket, mirroring the OHLC shape used by
+/// candle-indexing services.
+#[derive(Debug, Clone)]
+pub struct SpreadCandle {
+    pub bucket_start: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub samples: i64,
+}
+
+/// Append-only ingestion writer. Kept deliberately separate from the analytics
+/// query path so raw writes never contend with aggregation reads.
+pub struct SnapshotWriter {
+    conn: Connection,
+}
+
+impl SnapshotWriter {
+    /// Open (creating if needed) the snapshot store at `path`.
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).context("Failed to open snapshot store")?;
+        conn.execute_batch(SCHEMA).context("Failed to initialize schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Record a raw book snapshot with its wall-clock timestamp.
+    pub fn append_snapshot(&self, row: &SnapshotRow) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO snapshots (ts_ms, venue, market_id, best_bid, best_ask, depth, spread) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![row.ts_ms, row.venue, row.market_id, row.best_bid, row.best_ask, row.depth, row.spread],
+            )
+            .context("Failed to append snapshot")?;
+        Ok(())
+    }
+
+    /// Record a detected opportunity alongside the snapshot stream.
+    pub fn append_opportunity(&self, ts_ms: i64, market_id: &str, opp: &ArbitrageOpportunity) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO opportunities \
+                 (ts_ms, market_id, buy_platform, sell_platform, buy_price, sell_price, max_size, total_profit, fees, net_profit) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    ts_ms,
+                    market_id,
+                    opp.buy_platform,
+                    opp.sell_platform,
+                    opp.buy_price,
+                    opp.sell_price,
+                    opp.max_size,
+                    opp.total_profit,
+                    opp.fees,
+                    opp.net_profit,
+                ],
+            )
+            .context("Failed to append opportunity")?;
+        Ok(())
+    }
+}
+
+/// Read-only analytics path over the same store.
+pub struct AnalyticsStore {
+    conn: Connection,
+}
+
+impl AnalyticsStore {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).context("Failed to open analytics store")?;
+        Ok(Self { conn })
+    }
+
+    /// Aggregate a market's recorded spreads into OHLC-style candles of
+    /// `bucket_secs` width over `[start_ms, end_ms)`.
+    pub fn spread_candles(
+        &self,
+        market_id: &str,
+        start_ms: i64,
+        end_ms: i64,
+        bucket_secs: i64,
+    ) -> Result<Vec<SpreadCandle>> {
+        let bucket_ms = bucket_secs * 1000;
+        let mut stmt = self.conn.prepare(
+            "SELECT ts_ms, spread FROM snapshots \
+             WHERE market_id = ?1 AND ts_ms >= ?2 AND ts_ms < ?3 ORDER BY ts_ms ASC",
+        )?;
+        let rows = stmt.query_map(params![market_id, start_ms, end_ms], |r| {
+            Ok((r.get::<_, i64>(0)?, r.get::<_, f64>(1)?))
+        })?;
+
+        let mut candles: Vec<SpreadCandle> = Vec::new();
+        for row in rows {
+            let (ts_ms, spread) = row?;
+            let bucket_start = (ts_ms / bucket_ms) * bucket_ms;
+            match candles.last_mut() {
+                Some(candle) if candle.bucket_start == bucket_start => {
+                    candle.high = candle.high.max(spread);
+                    candle.low = candle.low.min(spread);
+                    candle.close = spread;
+                    candle.samples += 1;
+                }
+                _ => candles.push(SpreadCandle {
+                    bucket_start,
+                    open: spread,
+                    high: spread,
+                    low: spread,
+                    close: spread,
+                    samples: 1,
+                }),
+            }
+        }
+        Ok(candles)
+    }
+
+    /// Replay stored snapshots for `market_id` under a different fee assumption
+    /// so windows can be backtested without re-hitting the live venues. Each
+    /// item is `(ts_ms, (best_bid - best_ask) * 100 - fee_cents)`: the top-of-book
+    /// cross in cents net of fees, the negated `spread` column less `fee_cents`.
+    pub fn backfill_spreads(&self, market_id: &str, fee_cents: f64) -> Result<Vec<(i64, f64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT ts_ms, best_bid, best_ask FROM snapshots \
+             WHERE market_id = ?1 ORDER BY ts_ms ASC",
+        )?;
+        let rows = stmt.query_map(params![market_id], |r| {
+            Ok((r.get::<_, i64>(0)?, r.get::<_, f64>(1)?, r.get::<_, f64>(2)?))
+        })?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (ts_ms, best_bid, best_ask) = row?;
+            let net = (best_bid - best_ask) * 100.0 - fee_cents;
+            out.push((ts_ms, net));
+        }
+        Ok(out)
+    }
+}
+
+const SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS snapshots (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts_ms      INTEGER NOT NULL,
+    venue      TEXT NOT NULL,
+    market_id  TEXT NOT NULL,
+    best_bid   REAL NOT NULL,
+    best_ask   REAL NOT NULL,
+    depth      REAL NOT NULL,
+    spread     REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_snapshots_market_ts ON snapshots (market_id, ts_ms);
+CREATE TABLE IF NOT EXISTS opportunities (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts_ms         INTEGER NOT NULL,
+    market_id     TEXT NOT NULL,
+    buy_platform  TEXT NOT NULL,
+    sell_platform TEXT NOT NULL,
+    buy_price     REAL NOT NULL,
+    sell_price    REAL NOT NULL,
+    max_size      REAL NOT NULL,
+    total_profit  REAL NOT NULL,
+    fees          REAL NOT NULL,
+    net_profit    REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_opps_market_ts ON opportunities (market_id, ts_ms);
+";